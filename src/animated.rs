@@ -0,0 +1,238 @@
+//! Frame-based animations over registered textures (sprite sheets, decoded GIFs).
+
+use std::time::Duration;
+
+use crate::AtlasRegion;
+
+/// One frame of an [`AnimatedTexture`] and how long it stays on screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnimationFrame {
+    pub region: AtlasRegion,
+    pub duration: Duration,
+}
+
+/// Keeps track of animation timing and yields the texture region to draw each frame.
+#[derive(Clone, Debug)]
+pub struct AnimatedTexture {
+    frames: Vec<AnimationFrame>,
+    looping: bool,
+    elapsed: Duration,
+}
+
+impl AnimatedTexture {
+    pub fn new(frames: Vec<AnimationFrame>) -> Self {
+        Self {
+            frames,
+            looping: true,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Builds an animation from a whole registered texture per frame, e.g. the frames
+    /// of a decoded GIF.
+    pub fn from_textures<I>(frames: I, size: [f32; 2]) -> Self
+    where
+        I: IntoIterator<Item = (imgui::TextureId, Duration)>,
+    {
+        Self::new(
+            frames
+                .into_iter()
+                .map(|(texture_id, duration)| AnimationFrame {
+                    region: AtlasRegion {
+                        texture_id,
+                        uv0: [0.0, 0.0],
+                        uv1: [1.0, 1.0],
+                        size,
+                    },
+                    duration,
+                })
+                .collect(),
+        )
+    }
+
+    /// Builds an animation from a sprite sheet laid out left to right, top to bottom,
+    /// with `frame_count` frames of `frame_size` pixels each.
+    pub fn from_sprite_sheet(
+        texture_id: imgui::TextureId,
+        sheet_size: (u32, u32),
+        frame_size: (u32, u32),
+        frame_count: usize,
+        frame_duration: Duration,
+    ) -> Self {
+        let (sheet_width, sheet_height) = (sheet_size.0 as f32, sheet_size.1 as f32);
+        let (frame_width, frame_height) = frame_size;
+        let columns = (sheet_size.0 / frame_width.max(1)).max(1) as usize;
+
+        Self::new(
+            (0..frame_count)
+                .map(|index| {
+                    let x = (index % columns) as u32 * frame_width;
+                    let y = (index / columns) as u32 * frame_height;
+                    AnimationFrame {
+                        region: AtlasRegion {
+                            texture_id,
+                            uv0: [x as f32 / sheet_width, y as f32 / sheet_height],
+                            uv1: [
+                                (x + frame_width) as f32 / sheet_width,
+                                (y + frame_height) as f32 / sheet_height,
+                            ],
+                            size: [frame_width as f32, frame_height as f32],
+                        },
+                        duration: frame_duration,
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    pub fn frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+
+    /// Moves the animation forward by `delta`, usually the frame time.
+    pub fn advance(&mut self, delta: Duration) {
+        self.elapsed += delta;
+
+        let total = self.total_duration();
+        if total.is_zero() {
+            self.elapsed = Duration::ZERO;
+        } else if self.looping {
+            self.elapsed =
+                Duration::from_nanos((self.elapsed.as_nanos() % total.as_nanos()) as u64);
+        } else if self.elapsed > total {
+            self.elapsed = total;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed >= self.total_duration()
+    }
+
+    pub fn current_frame(&self) -> Option<&AnimationFrame> {
+        let mut remaining = self.elapsed;
+        for frame in &self.frames {
+            if remaining < frame.duration {
+                return Some(frame);
+            }
+            remaining -= frame.duration;
+        }
+        self.frames.last()
+    }
+
+    /// Draws the current frame at `size`, or at its native size when `None`.
+    pub fn build(&self, ui: &imgui::Ui, size: Option<[f32; 2]>) {
+        if let Some(frame) = self.current_frame() {
+            frame
+                .region
+                .image(size.unwrap_or(frame.region.size))
+                .build(ui);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animation(durations: &[u64]) -> AnimatedTexture {
+        AnimatedTexture::from_textures(
+            durations.iter().enumerate().map(|(index, &millis)| {
+                (
+                    imgui::TextureId::new(index + 1),
+                    Duration::from_millis(millis),
+                )
+            }),
+            [16.0, 16.0],
+        )
+    }
+
+    fn current_texture(animation: &AnimatedTexture) -> Option<usize> {
+        animation
+            .current_frame()
+            .map(|frame| frame.region.texture_id.id())
+    }
+
+    #[test]
+    fn steps_through_frames_by_duration() {
+        let mut animation = animation(&[100, 50, 100]);
+        assert_eq!(current_texture(&animation), Some(1));
+        animation.advance(Duration::from_millis(99));
+        assert_eq!(current_texture(&animation), Some(1));
+        animation.advance(Duration::from_millis(1));
+        assert_eq!(current_texture(&animation), Some(2));
+        animation.advance(Duration::from_millis(50));
+        assert_eq!(current_texture(&animation), Some(3));
+    }
+
+    #[test]
+    fn wraps_around_when_looping() {
+        let mut animation = animation(&[100, 50, 100]);
+        animation.advance(Duration::from_millis(250));
+        assert_eq!(current_texture(&animation), Some(1));
+        animation.advance(Duration::from_millis(3 * 250 + 120));
+        assert_eq!(current_texture(&animation), Some(2));
+        assert!(!animation.is_finished());
+    }
+
+    #[test]
+    fn stops_on_the_last_frame_without_looping() {
+        let mut animation = animation(&[100, 50]);
+        animation.set_looping(false);
+        animation.advance(Duration::from_millis(149));
+        assert!(!animation.is_finished());
+        animation.advance(Duration::from_secs(10));
+        assert!(animation.is_finished());
+        assert_eq!(current_texture(&animation), Some(2));
+
+        animation.reset();
+        assert_eq!(current_texture(&animation), Some(1));
+    }
+
+    #[test]
+    fn handles_animations_without_time() {
+        let mut empty = animation(&[]);
+        empty.advance(Duration::from_millis(10));
+        assert_eq!(current_texture(&empty), None);
+
+        let mut instant = animation(&[0, 0]);
+        instant.advance(Duration::from_millis(10));
+        assert_eq!(current_texture(&instant), Some(2));
+    }
+
+    #[test]
+    fn lays_out_sprite_sheets_in_rows() {
+        let animation = AnimatedTexture::from_sprite_sheet(
+            imgui::TextureId::new(1),
+            (64, 32),
+            (16, 16),
+            6,
+            Duration::from_millis(100),
+        );
+        let regions: Vec<_> = animation
+            .frames()
+            .iter()
+            .map(|frame| (frame.region.uv0, frame.region.uv1))
+            .collect();
+        assert_eq!(regions[0], ([0.0, 0.0], [0.25, 0.5]));
+        assert_eq!(regions[3], ([0.75, 0.0], [1.0, 0.5]));
+        assert_eq!(regions[5], ([0.25, 0.5], [0.5, 1.0]));
+        assert_eq!(animation.frames()[0].region.size, [16.0, 16.0]);
+        assert_eq!(animation.total_duration(), Duration::from_millis(600));
+    }
+}
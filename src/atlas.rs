@@ -0,0 +1,108 @@
+//! Sprite atlas support: one registered texture, many named regions.
+
+use std::collections::HashMap;
+
+use sdl2::rect::Rect;
+
+/// A named sub-rectangle of an atlas texture with precomputed UVs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasRegion {
+    pub texture_id: imgui::TextureId,
+    pub uv0: [f32; 2],
+    pub uv1: [f32; 2],
+    pub size: [f32; 2],
+}
+
+impl AtlasRegion {
+    /// Returns an image widget for this region, drawn at `size`.
+    pub fn image(&self, size: [f32; 2]) -> imgui::Image {
+        imgui::Image::new(self.texture_id, size)
+            .uv0(self.uv0)
+            .uv1(self.uv1)
+    }
+
+    /// Draws the region at its native pixel size.
+    pub fn build(&self, ui: &imgui::Ui) {
+        self.image(self.size).build(ui);
+    }
+}
+
+/// Lookup table of named regions inside a single registered texture.
+#[derive(Clone, Debug)]
+pub struct TextureAtlas {
+    texture_id: imgui::TextureId,
+    width: u32,
+    height: u32,
+    regions: HashMap<String, AtlasRegion>,
+}
+
+impl TextureAtlas {
+    pub fn new(texture_id: imgui::TextureId, width: u32, height: u32) -> Self {
+        Self {
+            texture_id,
+            width,
+            height,
+            regions: HashMap::new(),
+        }
+    }
+
+    pub fn texture_id(&self) -> imgui::TextureId {
+        self.texture_id
+    }
+
+    /// Defines (or redefines) a region in texture pixel coordinates.
+    pub fn add_region(&mut self, name: impl Into<String>, rect: Rect) -> AtlasRegion {
+        let (width, height) = (self.width as f32, self.height as f32);
+        let region = AtlasRegion {
+            texture_id: self.texture_id,
+            uv0: [rect.left() as f32 / width, rect.top() as f32 / height],
+            uv1: [rect.right() as f32 / width, rect.bottom() as f32 / height],
+            size: [rect.width() as f32, rect.height() as f32],
+        };
+
+        self.regions.insert(name.into(), region);
+        region
+    }
+
+    pub fn region(&self, name: &str) -> Option<AtlasRegion> {
+        self.regions.get(name).copied()
+    }
+
+    pub fn regions(&self) -> impl Iterator<Item = (&str, &AtlasRegion)> {
+        self.regions
+            .iter()
+            .map(|(name, region)| (name.as_str(), region))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_region_uvs_from_pixels() {
+        let mut atlas = TextureAtlas::new(imgui::TextureId::new(3), 256, 128);
+        let region = atlas.add_region("icon", Rect::new(64, 32, 32, 64));
+        assert_eq!(region.texture_id, imgui::TextureId::new(3));
+        assert_eq!(region.uv0, [0.25, 0.25]);
+        assert_eq!(region.uv1, [0.375, 0.75]);
+        assert_eq!(region.size, [32.0, 64.0]);
+    }
+
+    #[test]
+    fn covers_the_whole_texture() {
+        let mut atlas = TextureAtlas::new(imgui::TextureId::new(1), 100, 50);
+        let region = atlas.add_region("all", Rect::new(0, 0, 100, 50));
+        assert_eq!((region.uv0, region.uv1), ([0.0, 0.0], [1.0, 1.0]));
+    }
+
+    #[test]
+    fn redefines_regions_by_name() {
+        let mut atlas = TextureAtlas::new(imgui::TextureId::new(1), 100, 100);
+        atlas.add_region("button", Rect::new(0, 0, 10, 10));
+        let region = atlas.add_region("button", Rect::new(50, 50, 10, 10));
+        assert_eq!(atlas.region("button"), Some(region));
+        assert_eq!(atlas.regions().count(), 1);
+        assert_eq!(atlas.region("missing"), None);
+    }
+}
@@ -0,0 +1,138 @@
+//! Builder collecting the renderer's construction options.
+
+use sdl2::render::{BlendMode, Canvas, RenderTarget, TextureCreator, WindowCanvas};
+use sdl2::video::Window;
+
+use crate::{
+    ClipRounding, FontAtlasFormat, FontAtlasOptions, MissingTexturePolicy, Renderer, RendererError,
+    ScaleMode,
+};
+
+/// Configures a [`Renderer`] before it is created; see [`Renderer::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct RendererBuilder {
+    font_options: FontAtlasOptions,
+    missing_texture_policy: MissingTexturePolicy,
+    render_driver: Option<String>,
+    validate_draw_data: bool,
+    clip_rounding: ClipRounding,
+    scissor_clipping: Option<bool>,
+    pixel_snapping: bool,
+    premultiplied_alpha: bool,
+    blend_mode: Option<BlendMode>,
+}
+
+impl RendererBuilder {
+    pub fn font_options(mut self, font_options: FontAtlasOptions) -> Self {
+        self.font_options = font_options;
+        self
+    }
+
+    pub fn font_format(mut self, format: FontAtlasFormat) -> Self {
+        self.font_options.format = format;
+        self
+    }
+
+    pub fn font_scale_mode(mut self, scale_mode: ScaleMode) -> Self {
+        self.font_options.scale_mode = scale_mode;
+        self
+    }
+
+    pub fn premultiplied_font_alpha(mut self, premultiplied_alpha: bool) -> Self {
+        self.font_options.premultiplied_alpha = premultiplied_alpha;
+        self
+    }
+
+    /// Premultiplies the font atlas and the textures created through the renderer; see
+    /// [`Renderer::set_premultiplied_alpha`].
+    pub fn premultiplied_alpha(mut self, premultiplied_alpha: bool) -> Self {
+        self.font_options.premultiplied_alpha = premultiplied_alpha;
+        self.premultiplied_alpha = premultiplied_alpha;
+        self
+    }
+
+    /// See [`Renderer::set_default_blend_mode`].
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = Some(blend_mode);
+        self
+    }
+
+    pub fn missing_texture_policy(mut self, policy: MissingTexturePolicy) -> Self {
+        self.missing_texture_policy = policy;
+        self
+    }
+
+    /// See [`Renderer::set_validate_draw_data`].
+    pub fn validate_draw_data(mut self, validate: bool) -> Self {
+        self.validate_draw_data = validate;
+        self
+    }
+
+    pub fn clip_rounding(mut self, rounding: ClipRounding) -> Self {
+        self.clip_rounding = rounding;
+        self
+    }
+
+    /// See [`Renderer::set_scissor_clipping`]; on unless turned off here.
+    pub fn scissor_clipping(mut self, clip: bool) -> Self {
+        self.scissor_clipping = Some(clip);
+        self
+    }
+
+    /// See [`Renderer::set_pixel_snapping`].
+    pub fn pixel_snapping(mut self, snap: bool) -> Self {
+        self.pixel_snapping = snap;
+        self
+    }
+
+    /// Avoids subpixel sampling and scissoring, which some embedded GLES drivers (e.g. on
+    /// Raspberry Pi kiosks under KMSDRM) render with blurry or misplaced edges: the font
+    /// atlas uses nearest filtering, vertices are snapped to whole pixels and clip rects
+    /// are rounded outward. The drivers aren't detected, so opt in where needed.
+    pub fn gles_compatibility(mut self) -> Self {
+        self.font_options.scale_mode = ScaleMode::Nearest;
+        self.pixel_snapping = true;
+        self.clip_rounding = ClipRounding::Outward;
+        self
+    }
+
+    /// Requests a render driver (`"direct3d11"`, `"opengl"`, `"metal"`, `"software"`, ...)
+    /// through `SDL_HINT_RENDER_DRIVER`. The hint only affects canvases created after it
+    /// is set, so create the canvas with [`RendererBuilder::build_canvas`].
+    pub fn render_driver(mut self, driver: impl Into<String>) -> Self {
+        self.render_driver = Some(driver.into());
+        self
+    }
+
+    /// Applies the render driver hint, if any, and creates a canvas for `window`. Check
+    /// [`Renderer::driver_name`] for the driver SDL actually picked.
+    pub fn build_canvas(&self, window: Window) -> Result<WindowCanvas, RendererError> {
+        if let Some(driver) = &self.render_driver {
+            sdl2::hint::set("SDL_RENDER_DRIVER", driver);
+        }
+        window
+            .into_canvas()
+            .build()
+            .map_err(|error| RendererError::Sdl(error.to_string()))
+    }
+
+    pub fn build<'a, T: RenderTarget>(
+        self,
+        canvas: &mut Canvas<T>,
+        imgui_context: &mut imgui::Context,
+        texture_creator: &'a TextureCreator<T::Context>,
+    ) -> Result<Renderer<'a>, RendererError> {
+        let mut renderer =
+            Renderer::with_font_options(canvas, imgui_context, texture_creator, self.font_options)?;
+        renderer.set_missing_texture_policy(self.missing_texture_policy);
+        renderer.set_validate_draw_data(self.validate_draw_data);
+        renderer.set_clip_rounding(self.clip_rounding);
+        renderer.set_scissor_clipping(self.scissor_clipping.unwrap_or(true));
+        renderer.set_pixel_snapping(self.pixel_snapping);
+        renderer.set_premultiplied_alpha(self.premultiplied_alpha);
+        if let Some(blend_mode) = self.blend_mode {
+            renderer.set_default_blend_mode(blend_mode);
+        }
+        Ok(renderer)
+    }
+}
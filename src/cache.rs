@@ -0,0 +1,156 @@
+//! Managed texture cache that keeps GPU memory under a byte budget.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use sdl2::render::Texture;
+
+use crate::{texture_size_bytes, Renderer, RendererError};
+
+struct CacheEntry {
+    texture_id: imgui::TextureId,
+    bytes: usize,
+    // Renderer frame of the last `get`, see `Renderer::begin_frame`.
+    last_used: u64,
+}
+
+/// Loads textures on demand through a user callback and evicts the least recently
+/// used ones once the estimated memory usage exceeds the budget.
+///
+/// Textures are registered with (and unregistered from) the [`Renderer`] passed to
+/// each call, so the same renderer should be used for the lifetime of the cache.
+///
+/// Textures fetched during the current frame (see [`Renderer::begin_frame`]) may already
+/// be referenced by the frame's draw data, so they are never evicted; the cache can go
+/// over budget until the next frame.
+pub struct TextureCache<K, F> {
+    budget_bytes: usize,
+    used_bytes: usize,
+    entries: HashMap<K, CacheEntry>,
+    loader: F,
+}
+
+impl<K, F> TextureCache<K, F>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn with_budget_bytes(budget_bytes: usize, loader: F) -> Self {
+        Self {
+            budget_bytes,
+            used_bytes: 0,
+            entries: HashMap::new(),
+            loader,
+        }
+    }
+
+    /// Returns the texture for `key`, loading it first if it isn't resident or has been
+    /// unregistered from the renderer (e.g. by [`Renderer::clear_textures`]). Call this
+    /// every frame the texture is drawn so it is kept as recently used.
+    pub fn get<'a>(
+        &mut self,
+        renderer: &mut Renderer<'a>,
+        key: &K,
+    ) -> Result<imgui::TextureId, RendererError>
+    where
+        F: FnMut(&K) -> Result<Texture<'a>, RendererError>,
+    {
+        let frame = renderer.frame;
+
+        if let Some(entry) = self.entries.get_mut(key) {
+            if renderer.texture_map.get(entry.texture_id).is_some() {
+                entry.last_used = frame;
+                return Ok(entry.texture_id);
+            }
+        }
+        if let Some(stale) = self.entries.remove(key) {
+            self.used_bytes -= stale.bytes;
+        }
+
+        let texture = (self.loader)(key)?;
+        let bytes = texture_size_bytes(&texture);
+        let texture_id = renderer.register_texture(texture);
+
+        self.used_bytes += bytes;
+        self.entries.insert(
+            key.clone(),
+            CacheEntry {
+                texture_id,
+                bytes,
+                last_used: frame,
+            },
+        );
+
+        self.evict_over_budget(renderer);
+
+        Ok(texture_id)
+    }
+
+    /// Evicts least recently used textures until the cache fits its budget, sparing the
+    /// ones fetched during the current frame.
+    fn evict_over_budget(&mut self, renderer: &mut Renderer) {
+        let frame = renderer.frame;
+
+        while self.used_bytes > self.budget_bytes {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.last_used < frame)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+
+            match oldest {
+                Some(oldest) => {
+                    self.evict(renderer, &oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Unregisters and drops the texture cached for `key`, if any.
+    pub fn evict(&mut self, renderer: &mut Renderer, key: &K) -> bool {
+        match self.entries.remove(key) {
+            Some(entry) => {
+                self.used_bytes -= entry.bytes;
+                renderer.unregister_texture(entry.texture_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Evicts every cached texture.
+    pub fn clear(&mut self, renderer: &mut Renderer) {
+        for (_, entry) in self.entries.drain() {
+            renderer.unregister_texture(entry.texture_id);
+        }
+        self.used_bytes = 0;
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+
+    pub fn budget_bytes(&self) -> usize {
+        self.budget_bytes
+    }
+
+    /// Changes the budget, evicting textures right away if the cache no longer fits.
+    /// Textures fetched during the current frame stay until the next one.
+    pub fn set_budget_bytes(&mut self, renderer: &mut Renderer, budget_bytes: usize) {
+        self.budget_bytes = budget_bytes;
+        self.evict_over_budget(renderer);
+    }
+}
@@ -0,0 +1,94 @@
+//! SDL functions newer than the renderer's minimum. A regular build links
+//! `SDL_RenderGeometryRaw` and so already requires SDL 2.0.18, which has all of them; with
+//! `legacy-sdl` they are looked up in the loaded SDL library at runtime instead, so one
+//! binary works with old and new releases alike.
+
+#[cfg(feature = "legacy-sdl")]
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_int;
+#[cfg(feature = "legacy-sdl")]
+use std::ptr::null;
+#[cfg(feature = "legacy-sdl")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "legacy-sdl")]
+use sdl2::sys::{SDL_LoadFunction, SDL_LoadObject};
+use sdl2::sys::{SDL_Rect, SDL_Renderer, SDL_ScaleMode, SDL_Texture};
+
+type SetTextureScaleMode = unsafe extern "C" fn(*mut SDL_Texture, SDL_ScaleMode) -> c_int;
+type UpdateNvTexture = unsafe extern "C" fn(
+    *mut SDL_Texture,
+    *const SDL_Rect,
+    *const u8,
+    c_int,
+    *const u8,
+    c_int,
+) -> c_int;
+type RenderSetVSync = unsafe extern "C" fn(*mut SDL_Renderer, c_int) -> c_int;
+
+/// Entry points of the SDL in use, `None` where it is too old to have them.
+pub(crate) struct OptionalFunctions {
+    /// SDL 2.0.12.
+    pub(crate) set_texture_scale_mode: Option<SetTextureScaleMode>,
+    /// SDL 2.0.16.
+    pub(crate) update_nv_texture: Option<UpdateNvTexture>,
+    /// SDL 2.0.18.
+    pub(crate) render_set_vsync: Option<RenderSetVSync>,
+}
+
+#[cfg(not(feature = "legacy-sdl"))]
+pub(crate) fn optional_functions() -> &'static OptionalFunctions {
+    static FUNCTIONS: OptionalFunctions = OptionalFunctions {
+        set_texture_scale_mode: Some(sdl2::sys::SDL_SetTextureScaleMode as SetTextureScaleMode),
+        update_nv_texture: Some(sdl2::sys::SDL_UpdateNVTexture as UpdateNvTexture),
+        render_set_vsync: Some(sdl2::sys::SDL_RenderSetVSync as RenderSetVSync),
+    };
+    &FUNCTIONS
+}
+
+#[cfg(feature = "legacy-sdl")]
+pub(crate) fn optional_functions() -> &'static OptionalFunctions {
+    static FUNCTIONS: OnceLock<OptionalFunctions> = OnceLock::new();
+    FUNCTIONS.get_or_init(|| unsafe {
+        let library = load_sdl();
+        let lookup = |name: &CStr| -> Option<*mut c_void> {
+            if library.is_null() {
+                return None;
+            }
+            let function = SDL_LoadFunction(library, name.as_ptr());
+            (!function.is_null()).then_some(function)
+        };
+
+        OptionalFunctions {
+            set_texture_scale_mode: lookup(c"SDL_SetTextureScaleMode")
+                .map(|function| std::mem::transmute::<*mut c_void, SetTextureScaleMode>(function)),
+            update_nv_texture: lookup(c"SDL_UpdateNVTexture")
+                .map(|function| std::mem::transmute::<*mut c_void, UpdateNvTexture>(function)),
+            render_set_vsync: lookup(c"SDL_RenderSetVSync")
+                .map(|function| std::mem::transmute::<*mut c_void, RenderSetVSync>(function)),
+        }
+    })
+}
+
+/// Returns a handle to the SDL library the process already uses, or null where none can
+/// be opened, in which case every optional function is missing. The handle is never
+/// released.
+#[cfg(feature = "legacy-sdl")]
+unsafe fn load_sdl() -> *mut c_void {
+    // A null name searches the global symbols of the process. That finds a shared SDL,
+    // but a statically linked one only when the executable exports its symbols (e.g.
+    // with `-rdynamic`). Windows has no such lookup, so the DLL is opened by name there,
+    // which fails for a static build. Emscripten has no dynamic loading at all.
+    let name = if cfg!(windows) {
+        c"SDL2.dll".as_ptr()
+    } else {
+        null()
+    };
+    let library = SDL_LoadObject(name);
+    if library.is_null() {
+        // `SDL_LoadObject` reports the failure through the SDL error, which would make
+        // later errors confusing.
+        sdl2::sys::SDL_ClearError();
+    }
+    library
+}
@@ -0,0 +1,136 @@
+//! Error type shared by the renderer and its helpers.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+
+#[derive(Debug)]
+pub enum RendererError {
+    /// SDL failed to create a texture or the surface it is created from.
+    TextureCreation(String),
+    /// SDL failed to upload pixels into (or lock) an existing texture.
+    TextureUpload(String),
+    /// The supplied pixel data doesn't cover the texture or region being written.
+    InvalidPixelData(String),
+    /// The texture is larger than the renderer's maximum texture size.
+    TextureTooLarge {
+        width: u32,
+        height: u32,
+        max_width: u32,
+        max_height: u32,
+    },
+    /// A draw command refers to vertices or indices outside its draw list.
+    InvalidDrawData(String),
+    /// No texture is registered under the id.
+    TextureNotFound(imgui::TextureId),
+    /// The id belonged to a texture that has been unregistered.
+    StaleTexture(imgui::TextureId),
+    /// The texture is owned by foreign code and can't be replaced in place.
+    ExternalTexture(imgui::TextureId),
+    /// The SDL renderer can't render into textures.
+    RenderTargetsUnsupported,
+    /// The SDL library loaded at runtime is too old for the requested operation.
+    UnsupportedSdlVersion {
+        linked: sdl2::version::Version,
+        required: sdl2::version::Version,
+    },
+    /// The named SDL function failed, with the message from `SDL_GetError`.
+    SdlCall { call: &'static str, message: String },
+    /// Drawing one command failed; `draw_list` and `command` index into the draw data.
+    DrawCommand {
+        draw_list: usize,
+        command: usize,
+        source: Box<RendererError>,
+    },
+    /// Any other failing SDL operation, with the message reported by the `sdl2` crate.
+    Sdl(String),
+    #[cfg(feature = "image")]
+    Image(image::ImageError),
+}
+
+impl Error for RendererError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::DrawCommand { source, .. } => Some(source.as_ref()),
+            #[cfg(feature = "image")]
+            Self::Image(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TextureCreation(msg) => write!(f, "Error creating texture: {}", msg),
+            Self::TextureUpload(msg) => write!(f, "Error uploading texture data: {}", msg),
+            Self::InvalidPixelData(msg) => write!(f, "Invalid pixel data: {}", msg),
+            Self::TextureTooLarge {
+                width,
+                height,
+                max_width,
+                max_height,
+            } => write!(
+                f,
+                "Texture {}x{} exceeds the renderer's maximum texture size {}x{}",
+                width, height, max_width, max_height
+            ),
+            Self::InvalidDrawData(msg) => write!(f, "Invalid draw data: {}", msg),
+            Self::TextureNotFound(texture_id) => {
+                write!(f, "No texture registered for {:?}", texture_id)
+            }
+            Self::StaleTexture(texture_id) => {
+                write!(f, "{:?} refers to an unregistered texture", texture_id)
+            }
+            Self::ExternalTexture(texture_id) => write!(
+                f,
+                "{:?} is owned externally and has to be unregistered first",
+                texture_id
+            ),
+            Self::RenderTargetsUnsupported => {
+                write!(f, "Render targets are not supported by this renderer")
+            }
+            Self::UnsupportedSdlVersion { linked, required } => write!(
+                f,
+                "SDL {} is older than the required version {}",
+                linked, required
+            ),
+            Self::SdlCall { call, message } => write!(f, "{} failed: {}", call, message),
+            Self::DrawCommand {
+                draw_list,
+                command,
+                source,
+            } => write!(
+                f,
+                "Error drawing command {} of draw list {}: {}",
+                command, draw_list, source
+            ),
+            Self::Sdl(msg) => write!(f, "SDL error: {}", msg),
+            #[cfg(feature = "image")]
+            Self::Image(error) => write!(f, "Error decoding image: {}", error),
+        }
+    }
+}
+
+impl RendererError {
+    pub(crate) fn at_command(self, draw_list: usize, command: usize) -> Self {
+        Self::DrawCommand {
+            draw_list,
+            command,
+            source: Box::new(self),
+        }
+    }
+}
+
+/// SDL reports most failures as a bare message from `SDL_GetError`.
+impl From<String> for RendererError {
+    fn from(msg: String) -> Self {
+        Self::Sdl(msg)
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::ImageError> for RendererError {
+    fn from(error: image::ImageError) -> Self {
+        Self::Image(error)
+    }
+}
@@ -0,0 +1,528 @@
+//! Stand-ins for `SDL_RenderGeometry` and `SDL_RenderGeometryRaw` (SDL 2.0.18), used
+//! instead of `sdl2::sys` with the `legacy-sdl` feature so the binary loads on SDL 2.0.10
+//! (e.g. Ubuntu 20.04's). Like SDL, they return 0 on success and set the SDL error
+//! otherwise.
+//!
+//! Pairs of triangles
+//! forming an axis-aligned rectangle, which is what Dear ImGui emits for text, images and
+//! plain rects, are drawn exactly with `SDL_RenderCopyExF` or `SDL_RenderFillRectF`.
+//! Every other triangle is filled row by row with the average of its vertex colors, so
+//! rounded corners and anti-aliased edges look blockier and textured triangles that
+//! aren't part of such a rectangle lose their texture. With an [`crate::OutputRotation`]
+//! of 90 or 270 degrees no textured rectangle is recognized.
+
+#![allow(non_snake_case)]
+
+use std::ffi::c_void;
+use std::os::raw::c_int;
+use std::ptr::{null, null_mut};
+
+use sdl2::sys::{
+    SDL_Color, SDL_FPoint, SDL_FRect, SDL_GetTextureAlphaMod, SDL_GetTextureColorMod,
+    SDL_QueryTexture, SDL_Rect, SDL_RenderFillRectF, SDL_RenderFillRectsF, SDL_Renderer,
+    SDL_SetRenderDrawColor, SDL_SetTextureAlphaMod, SDL_SetTextureColorMod, SDL_Texture,
+    SDL_Vertex,
+};
+
+/// Positions and texture coordinates closer than this count as equal when matching
+/// rectangle corners.
+const EPSILON: f32 = 1.0 / 1024.0;
+
+const FLIP_HORIZONTAL: u32 = 1;
+const FLIP_VERTICAL: u32 = 2;
+
+// Declared here because `sdl2::sys` has `SDL_RendererFlip` as a Rust enum, which cannot
+// hold both flips at once.
+extern "C" {
+    #[link_name = "SDL_RenderCopyExF"]
+    fn render_copy_ex_f(
+        renderer: *mut SDL_Renderer,
+        texture: *mut SDL_Texture,
+        srcrect: *const SDL_Rect,
+        dstrect: *const SDL_FRect,
+        angle: f64,
+        center: *const c_void,
+        flip: u32,
+    ) -> c_int;
+}
+
+/// An axis-aligned rectangle with one color, and texture coordinates for its
+/// top-left and bottom-right corners.
+struct Quad {
+    min: [f32; 2],
+    max: [f32; 2],
+    uv_min: [f32; 2],
+    uv_max: [f32; 2],
+    color: [u8; 4],
+}
+
+pub(crate) unsafe fn SDL_RenderGeometry(
+    renderer: *mut SDL_Renderer,
+    texture: *mut SDL_Texture,
+    vertices: *const SDL_Vertex,
+    num_vertices: c_int,
+    indices: *const c_int,
+    num_indices: c_int,
+) -> c_int {
+    let vertices = std::slice::from_raw_parts(vertices, num_vertices as usize);
+    let indices = (!indices.is_null())
+        .then(|| std::slice::from_raw_parts(indices.cast::<u32>(), num_indices as usize));
+    status(render_geometry(renderer, texture, vertices, indices))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) unsafe fn SDL_RenderGeometryRaw(
+    renderer: *mut SDL_Renderer,
+    texture: *mut SDL_Texture,
+    xy: *const f32,
+    xy_stride: c_int,
+    color: *const SDL_Color,
+    color_stride: c_int,
+    uv: *const f32,
+    uv_stride: c_int,
+    num_vertices: c_int,
+    indices: *const c_void,
+    num_indices: c_int,
+    size_indices: c_int,
+) -> c_int {
+    let count = num_indices.max(0) as usize;
+    let mut indices: Option<Vec<u32>> = match size_indices {
+        _ if indices.is_null() => None,
+        1 => Some(
+            (0..count)
+                .map(|i| *indices.cast::<u8>().add(i) as u32)
+                .collect(),
+        ),
+        2 => Some(
+            (0..count)
+                .map(|i| *indices.cast::<u16>().add(i) as u32)
+                .collect(),
+        ),
+        _ => Some(std::slice::from_raw_parts(indices.cast::<u32>(), count).to_vec()),
+    };
+
+    // The renderer passes every vertex of a draw list along with the indices of a single
+    // command, so only the range those indices span is converted, and the indices are
+    // rebased onto it. Indices past `num_vertices` stay out of range and are skipped.
+    let num_vertices = num_vertices.max(0) as u32;
+    let (first, end) = match &mut indices {
+        Some(indices) => {
+            let first = indices.iter().copied().min().unwrap_or(0);
+            let end = indices
+                .iter()
+                .map(|&index| index.saturating_add(1))
+                .max()
+                .unwrap_or(0)
+                .min(num_vertices);
+            for index in indices.iter_mut() {
+                *index -= first;
+            }
+            (first, end.max(first))
+        }
+        None => (0, num_vertices),
+    };
+
+    let at = |base: *const f32, stride: c_int, index: usize| {
+        let point = base.cast::<u8>().add(index * stride as usize).cast::<f32>();
+        SDL_FPoint {
+            x: point.read_unaligned(),
+            y: point.add(1).read_unaligned(),
+        }
+    };
+    let vertices: Vec<SDL_Vertex> = (first as usize..end as usize)
+        .map(|index| SDL_Vertex {
+            position: at(xy, xy_stride, index),
+            color: color
+                .cast::<u8>()
+                .add(index * color_stride as usize)
+                .cast::<SDL_Color>()
+                .read_unaligned(),
+            tex_coord: if uv.is_null() {
+                SDL_FPoint { x: 0.0, y: 0.0 }
+            } else {
+                at(uv, uv_stride, index)
+            },
+        })
+        .collect();
+
+    status(render_geometry(
+        renderer,
+        texture,
+        &vertices,
+        indices.as_deref(),
+    ))
+}
+
+/// The SDL error has already been set by whichever call failed.
+fn status(result: Result<(), ()>) -> c_int {
+    match result {
+        Ok(()) => 0,
+        Err(()) => -1,
+    }
+}
+
+/// Draws triangles like `SDL_RenderGeometry` would, within the limits described in the
+/// module docs. `texture` may be null for untextured geometry.
+unsafe fn render_geometry(
+    renderer: *mut SDL_Renderer,
+    texture: *mut SDL_Texture,
+    vertices: &[SDL_Vertex],
+    indices: Option<&[u32]>,
+) -> Result<(), ()> {
+    let count = indices.map_or(vertices.len(), <[_]>::len) / 3;
+    let triangle = |index: usize| -> Option<[&SDL_Vertex; 3]> {
+        let corner = |corner: usize| match indices {
+            Some(indices) => vertices.get(indices[index * 3 + corner] as usize),
+            None => vertices.get(index * 3 + corner),
+        };
+        Some([corner(0)?, corner(1)?, corner(2)?])
+    };
+
+    let mut texture_size = [0.0; 2];
+    if !texture.is_null() {
+        let (mut width, mut height) = (0, 0);
+        if SDL_QueryTexture(texture, null_mut(), null_mut(), &mut width, &mut height) != 0 {
+            return Err(());
+        }
+        texture_size = [width as f32, height as f32];
+    }
+
+    let mut spans = Vec::new();
+    let mut index = 0;
+    while index < count {
+        let first = match triangle(index) {
+            Some(first) => first,
+            None => {
+                index += 1;
+                continue;
+            }
+        };
+
+        let quad = Some(index + 1)
+            .filter(|&next| next < count)
+            .and_then(triangle)
+            .and_then(|second| as_quad(first, second, !texture.is_null()));
+        if let Some(quad) = quad {
+            draw_quad(renderer, texture, texture_size, &quad)?;
+            index += 2;
+        } else {
+            fill_triangle(renderer, first, &mut spans)?;
+            index += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Recognizes two triangles splitting an axis-aligned rectangle along a diagonal, with
+/// one color and texture coordinates that are a linear function of the corner.
+fn as_quad(first: [&SDL_Vertex; 3], second: [&SDL_Vertex; 3], textured: bool) -> Option<Quad> {
+    let corners: Vec<&SDL_Vertex> = first.iter().chain(&second).copied().collect();
+    let color = rgba(corners[0]);
+    if corners.iter().any(|vertex| rgba(vertex) != color) {
+        return None;
+    }
+
+    let min = [
+        corners
+            .iter()
+            .map(|v| v.position.x)
+            .fold(f32::MAX, f32::min),
+        corners
+            .iter()
+            .map(|v| v.position.y)
+            .fold(f32::MAX, f32::min),
+    ];
+    let max = [
+        corners
+            .iter()
+            .map(|v| v.position.x)
+            .fold(f32::MIN, f32::max),
+        corners
+            .iter()
+            .map(|v| v.position.y)
+            .fold(f32::MIN, f32::max),
+    ];
+    if max[0] - min[0] < EPSILON || max[1] - min[1] < EPSILON {
+        return None;
+    }
+
+    // Corner ids 0..4 are `x + 2 * y` with 0 for the minimum and 1 for the maximum side.
+    let side = |value: f32, min: f32, max: f32| {
+        if (value - min).abs() < EPSILON {
+            Some(0)
+        } else if (value - max).abs() < EPSILON {
+            Some(1)
+        } else {
+            None
+        }
+    };
+    let mut uvs: [[Option<f32>; 2]; 2] = [[None; 2]; 2];
+    let mut ids = [0; 6];
+    for (id, vertex) in ids.iter_mut().zip(&corners) {
+        let x = side(vertex.position.x, min[0], max[0])?;
+        let y = side(vertex.position.y, min[1], max[1])?;
+        *id = x + 2 * y;
+
+        if textured {
+            for (axis, (side, uv)) in [(x, vertex.tex_coord.x), (y, vertex.tex_coord.y)]
+                .into_iter()
+                .enumerate()
+            {
+                match uvs[axis][side] {
+                    Some(known) if (known - uv).abs() >= EPSILON => return None,
+                    Some(_) => {}
+                    None => uvs[axis][side] = Some(uv),
+                }
+            }
+        }
+    }
+
+    // Each triangle has three distinct corners, and the corners they leave out are
+    // diagonally opposite (ids adding up to 3), so together they cover the rectangle
+    // exactly once.
+    let distinct = |ids: &[usize]| ids[0] != ids[1] && ids[1] != ids[2] && ids[0] != ids[2];
+    let missing = |ids: &[usize]| 6 - ids.iter().sum::<usize>();
+    if !distinct(&ids[..3]) || !distinct(&ids[3..]) || missing(&ids[..3]) + missing(&ids[3..]) != 3
+    {
+        return None;
+    }
+
+    let uv = |axis: usize, side: usize| uvs[axis][side].unwrap_or(0.0);
+    Some(Quad {
+        min,
+        max,
+        uv_min: [uv(0, 0), uv(1, 0)],
+        uv_max: [uv(0, 1), uv(1, 1)],
+        color,
+    })
+}
+
+unsafe fn draw_quad(
+    renderer: *mut SDL_Renderer,
+    texture: *mut SDL_Texture,
+    texture_size: [f32; 2],
+    quad: &Quad,
+) -> Result<(), ()> {
+    let [r, g, b, a] = quad.color;
+    let dst = SDL_FRect {
+        x: quad.min[0],
+        y: quad.min[1],
+        w: quad.max[0] - quad.min[0],
+        h: quad.max[1] - quad.min[1],
+    };
+
+    if texture.is_null() {
+        if SDL_SetRenderDrawColor(renderer, r, g, b, a) != 0
+            || SDL_RenderFillRectF(renderer, &dst) != 0
+        {
+            return Err(());
+        }
+        return Ok(());
+    }
+
+    // Texture coordinates decreasing along an axis mean the texture is mirrored on it;
+    // equal ones (e.g. Dear ImGui's white pixel) sample a single texel.
+    let mut flip = 0;
+    let mut src = [0; 4];
+    for axis in 0..2 {
+        let (from, to) = (quad.uv_min[axis], quad.uv_max[axis]);
+        if to < from {
+            flip |= [FLIP_HORIZONTAL, FLIP_VERTICAL][axis];
+        }
+        let start = (from.min(to) * texture_size[axis]).round();
+        let end = (from.max(to) * texture_size[axis]).round();
+        src[axis] = start.min(texture_size[axis] - 1.0) as c_int;
+        src[axis + 2] = ((end - start) as c_int).max(1);
+    }
+    let src = SDL_Rect {
+        x: src[0],
+        y: src[1],
+        w: src[2],
+        h: src[3],
+    };
+
+    let (mut old_r, mut old_g, mut old_b, mut old_a) = (255, 255, 255, 255);
+    SDL_GetTextureColorMod(texture, &mut old_r, &mut old_g, &mut old_b);
+    SDL_GetTextureAlphaMod(texture, &mut old_a);
+    SDL_SetTextureColorMod(texture, r, g, b);
+    SDL_SetTextureAlphaMod(texture, a);
+
+    let result = render_copy_ex_f(renderer, texture, &src, &dst, 0.0, null(), flip);
+
+    SDL_SetTextureColorMod(texture, old_r, old_g, old_b);
+    SDL_SetTextureAlphaMod(texture, old_a);
+    if result != 0 {
+        return Err(());
+    }
+    Ok(())
+}
+
+/// Fills the pixel rows whose centers lie inside the triangle with its average color.
+unsafe fn fill_triangle(
+    renderer: *mut SDL_Renderer,
+    triangle: [&SDL_Vertex; 3],
+    spans: &mut Vec<SDL_FRect>,
+) -> Result<(), ()> {
+    let points = triangle.map(|vertex| [vertex.position.x, vertex.position.y]);
+    if points.iter().flatten().any(|value| !value.is_finite()) {
+        return Ok(());
+    }
+
+    let mut color = [0u32; 4];
+    for vertex in triangle {
+        for (sum, channel) in color.iter_mut().zip(rgba(vertex)) {
+            *sum += channel as u32;
+        }
+    }
+    let [r, g, b, a] = color.map(|sum| ((sum + 1) / 3) as u8);
+    if a == 0 {
+        return Ok(());
+    }
+
+    let min_y = points.iter().map(|p| p[1]).fold(f32::MAX, f32::min);
+    let max_y = points.iter().map(|p| p[1]).fold(f32::MIN, f32::max);
+
+    spans.clear();
+    let mut row = (min_y - 0.5).ceil();
+    while row + 0.5 <= max_y {
+        let center = row + 0.5;
+        let mut left = f32::MAX;
+        let mut right = f32::MIN;
+        for edge in 0..3 {
+            let (from, to) = (points[edge], points[(edge + 1) % 3]);
+            if (from[1] <= center) != (to[1] <= center) {
+                let t = (center - from[1]) / (to[1] - from[1]);
+                let x = from[0] + t * (to[0] - from[0]);
+                left = left.min(x);
+                right = right.max(x);
+            }
+        }
+        if right > left {
+            spans.push(SDL_FRect {
+                x: left,
+                y: row,
+                w: right - left,
+                h: 1.0,
+            });
+        }
+        row += 1.0;
+    }
+
+    if spans.is_empty() {
+        return Ok(());
+    }
+    if SDL_SetRenderDrawColor(renderer, r, g, b, a) != 0
+        || SDL_RenderFillRectsF(renderer, spans.as_ptr(), spans.len() as c_int) != 0
+    {
+        return Err(());
+    }
+    Ok(())
+}
+
+fn rgba(vertex: &SDL_Vertex) -> [u8; 4] {
+    let color = vertex.color;
+    [color.r, color.g, color.b, color.a]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(x: f32, y: f32, u: f32, v: f32) -> SDL_Vertex {
+        SDL_Vertex {
+            position: SDL_FPoint { x, y },
+            color: SDL_Color {
+                r: 255,
+                g: 128,
+                b: 0,
+                a: 255,
+            },
+            tex_coord: SDL_FPoint { x: u, y: v },
+        }
+    }
+
+    /// The corners of the rect from (10, 20) to (50, 40) the way Dear ImGui's `PrimRectUV`
+    /// emits them: top-left, top-right, bottom-right, bottom-left.
+    fn rect() -> [SDL_Vertex; 4] {
+        [
+            vertex(10.0, 20.0, 0.0, 0.0),
+            vertex(50.0, 20.0, 1.0, 0.0),
+            vertex(50.0, 40.0, 1.0, 0.5),
+            vertex(10.0, 40.0, 0.0, 0.5),
+        ]
+    }
+
+    fn triangles(vertices: &[SDL_Vertex], indices: [usize; 6]) -> [[&SDL_Vertex; 3]; 2] {
+        let corner = |index: usize| &vertices[indices[index]];
+        [
+            [corner(0), corner(1), corner(2)],
+            [corner(3), corner(4), corner(5)],
+        ]
+    }
+
+    #[test]
+    fn recognizes_imgui_rects() {
+        let vertices = rect();
+        let [first, second] = triangles(&vertices, [0, 1, 2, 0, 2, 3]);
+        let quad = as_quad(first, second, true).unwrap();
+        assert_eq!((quad.min, quad.max), ([10.0, 20.0], [50.0, 40.0]));
+        assert_eq!((quad.uv_min, quad.uv_max), ([0.0, 0.0], [1.0, 0.5]));
+        assert_eq!(quad.color, [255, 128, 0, 255]);
+    }
+
+    #[test]
+    fn recognizes_either_diagonal_and_winding() {
+        let vertices = rect();
+        for indices in [[0, 1, 3, 1, 2, 3], [2, 1, 0, 3, 2, 0], [3, 0, 2, 2, 0, 1]] {
+            let [first, second] = triangles(&vertices, indices);
+            assert!(as_quad(first, second, true).is_some(), "{:?}", indices);
+        }
+    }
+
+    #[test]
+    fn keeps_flipped_texture_coordinates() {
+        let mut vertices = rect();
+        for vertex in &mut vertices {
+            vertex.tex_coord.x = 1.0 - vertex.tex_coord.x;
+        }
+        let [first, second] = triangles(&vertices, [0, 1, 2, 0, 2, 3]);
+        let quad = as_quad(first, second, true).unwrap();
+        assert_eq!((quad.uv_min, quad.uv_max), ([1.0, 0.0], [0.0, 0.5]));
+    }
+
+    #[test]
+    fn rejects_triangles_covering_the_same_half() {
+        let vertices = rect();
+        let [first, second] = triangles(&vertices, [0, 1, 2, 2, 1, 0]);
+        assert!(as_quad(first, second, false).is_none());
+    }
+
+    #[test]
+    fn rejects_non_rectangular_shapes() {
+        let mut vertices = rect();
+        vertices[2].position.x = 45.0;
+        let [first, second] = triangles(&vertices, [0, 1, 2, 0, 2, 3]);
+        assert!(as_quad(first, second, false).is_none());
+
+        let degenerate = [vertex(10.0, 20.0, 0.0, 0.0); 4];
+        let [first, second] = triangles(&degenerate, [0, 1, 2, 0, 2, 3]);
+        assert!(as_quad(first, second, false).is_none());
+    }
+
+    #[test]
+    fn rejects_gradients() {
+        let mut vertices = rect();
+        vertices[3].color.a = 0;
+        let [first, second] = triangles(&vertices, [0, 1, 2, 0, 2, 3]);
+        assert!(as_quad(first, second, false).is_none());
+    }
+
+    #[test]
+    fn ignores_texture_coordinates_of_untextured_rects() {
+        let mut vertices = rect();
+        vertices[2].tex_coord = SDL_FPoint { x: 0.3, y: 0.7 };
+        let [first, second] = triangles(&vertices, [0, 1, 2, 0, 2, 3]);
+        assert!(as_quad(first, second, true).is_none());
+        assert!(as_quad(first, second, false).is_some());
+    }
+}
@@ -1,193 +1,3662 @@
 //! SDL2-based imgui renderer implementation.
 //! Note: Requires SDL2 version 2.0.20+
+//!
+//! Per-texture scale modes, NV12/NV21 uploads and vsync toggling are linked like
+//! `SDL_RenderGeometryRaw` itself. Under the `legacy-sdl` feature they are looked up in
+//! the loaded SDL at runtime instead, so the binary also loads on releases that lack
+//! them: scale modes then fall back to SDL's default filtering, while
+//! [`Renderer::set_vsync`] and [`Renderer::update_texture_nv`] report
+//! [`RendererError::UnsupportedSdlVersion`]. The lookup only sees a shared SDL (or a
+//! static one whose symbols the executable exports), so elsewhere the functions count as
+//! missing. [`Renderer::sdl_features`] tells which are available.
+//!
+//! Any `Canvas<T>` works as a render target, so besides window canvases the UI can be
+//! drawn into a `Surface` canvas for headless/offscreen use.
+//!
+//! Enable the `image` feature to load textures straight from image files.
+//!
+//! The `legacy-sdl` feature lowers the requirement to SDL 2.0.10 by emulating
+//! `SDL_RenderGeometryRaw` with rectangle copies and fills. Text, images and plain
+//! rects come out as usual, but rounded corners and anti-aliased edges are drawn
+//! coarsely; only enable it for platforms stuck on such an SDL.
+//!
+//! The renderer needs nothing beyond `SDL_Renderer` itself, so it also works on
+//! `wasm32-unknown-emscripten` with Emscripten's SDL2 port (`-sUSE_SDL=2`, which is
+//! recent enough for `SDL_RenderGeometryRaw`). Canvas size and framebuffer scale are
+//! taken from the draw data, i.e. from whatever the platform backend reports for the
+//! browser window and its device pixel ratio.
+//!
+//! The renderer does not set `RENDERER_HAS_TEXTURES`: the imgui 0.8 bindings predate
+//! Dear ImGui's `ImTextureData` protocol, so the font atlas is uploaded once by
+//! [`Renderer::new`] and again on [`Renderer::reload_font_texture`].
+//!
+//! Baking the atlas itself happens inside Dear ImGui, which keeps the glyph tables in
+//! `ImFont` structures the bindings don't let us restore; the renderer therefore
+//! cannot cache a baked atlas on disk and skip that step.
+//!
+//! There is no C interface: the renderer is built around an `imgui::Context` and an
+//! `sdl2` canvas, and neither can wrap objects created by C or C++ code. C and C++
+//! applications should use Dear ImGui's own `imgui_impl_sdlrenderer` backend, which
+//! this crate follows.
 
-use std::mem::size_of;
-use std::ptr::null_mut;
+mod animated;
+mod atlas;
+mod builder;
+mod cache;
+mod dynamic;
+mod error;
+#[cfg(feature = "legacy-sdl")]
+mod legacy;
+mod owned;
+mod owned_renderer;
+mod store;
+#[cfg(test)]
+mod test_util;
+mod validate;
+mod widgets;
+
+pub use animated::{AnimatedTexture, AnimationFrame};
+pub use atlas::{AtlasRegion, TextureAtlas};
+pub use builder::RendererBuilder;
+pub use cache::TextureCache;
+pub use error::RendererError;
+pub use owned::OwnedDrawData;
+pub use owned_renderer::OwnedRenderer;
+pub use store::TextureStore;
+pub use validate::{validate_draw_data, DrawDataIssue, DrawDataIssueKind};
+pub use widgets::{tiled_image, UiExt, MAX_TILES};
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+use std::mem::{size_of, MaybeUninit};
+use std::os::raw::c_int;
+use std::ptr::{null, null_mut};
 
 use imgui::internal::RawWrapper;
 
-use sdl2::pixels::PixelFormatEnum;
+use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::Rect;
-use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
-use sdl2::sys::{SDL_Color, SDL_RenderGeometryRaw, SDL_ScaleMode, SDL_SetTextureScaleMode};
+use sdl2::render::{
+    BlendMode, Canvas, RenderTarget, Texture, TextureAccess, TextureCreator, WindowCanvas,
+};
+use sdl2::surface::{Surface, SurfaceRef};
+use sdl2::sys::{
+    SDL_BlendFactor, SDL_BlendOperation, SDL_Color, SDL_FPoint, SDL_GetRenderTarget,
+    SDL_GetRendererInfo, SDL_RenderFlush, SDL_Renderer, SDL_RendererFlags, SDL_RendererInfo,
+    SDL_ScaleMode, SDL_SetRenderTarget, SDL_Texture, SDL_Vertex,
+};
+#[cfg(not(feature = "legacy-sdl"))]
+use sdl2::sys::{SDL_RenderGeometry, SDL_RenderGeometryRaw};
+
+#[cfg(feature = "legacy-sdl")]
+use legacy::{SDL_RenderGeometry, SDL_RenderGeometryRaw};
 use sdl2::video::WindowContext;
 
-const RGBA32_BYTES: u32 = 4; // 4 bytes per pixel
+use owned::OwnedDrawCmd;
+
+/// Name the renderer reports to imgui through `set_renderer_name`.
+const RENDERER_NAME: &str = concat!("imgui-sdl2-renderer ", env!("CARGO_PKG_VERSION"));
+
+/// Backend flags the renderer sets on the imgui context.
+const BACKEND_FLAGS: imgui::BackendFlags = imgui::BackendFlags::RENDERER_HAS_VTX_OFFSET;
+
+/// Largest number of indices (or unindexed vertices) passed to one SDL geometry call;
+/// bigger commands are split, since some drivers fail on very large submissions.
+const MAX_GEOMETRY_INDICES: usize = 3 * 65_536;
+
+// Indices are handed to `SDL_RenderGeometryRaw` with their own size, which SDL accepts
+// for 16- and 32-bit indices, so builds of Dear ImGui with `ImDrawIdx = unsigned int`
+// work unchanged.
+const _: () = assert!(size_of::<imgui::DrawIdx>() == 2 || size_of::<imgui::DrawIdx>() == 4);
+
+/// Texture filtering used when a texture is drawn at a size other than its own.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScaleMode {
+    Nearest,
+    #[default]
+    Linear,
+    Best,
+}
+
+impl From<ScaleMode> for SDL_ScaleMode {
+    fn from(scale_mode: ScaleMode) -> Self {
+        match scale_mode {
+            ScaleMode::Nearest => SDL_ScaleMode::SDL_ScaleModeNearest,
+            ScaleMode::Linear => SDL_ScaleMode::SDL_ScaleModeLinear,
+            ScaleMode::Best => SDL_ScaleMode::SDL_ScaleModeBest,
+        }
+    }
+}
+
+/// Settings applied to a texture when it is registered with the renderer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureOptions {
+    pub scale_mode: ScaleMode,
+    pub blend_mode: BlendMode,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            scale_mode: ScaleMode::default(),
+            blend_mode: BlendMode::Blend,
+        }
+    }
+}
+
+/// What [`Renderer::render`] does with draw commands whose texture id isn't registered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissingTexturePolicy {
+    /// Draws the geometry untextured, i.e. as solid vertex-colored shapes.
+    #[default]
+    Untextured,
+    /// Skips the draw command.
+    Skip,
+    /// Stops rendering and returns [`RendererError::TextureNotFound`], or
+    /// [`RendererError::StaleTexture`] for an id that was unregistered.
+    Error,
+    /// Draws the given texture instead, e.g. one created with
+    /// [`Renderer::create_placeholder_texture`].
+    Placeholder(imgui::TextureId),
+}
+
+/// How clip rect edges at fractional pixel positions (e.g. at a 1.25 DPI scale) are
+/// turned into whole pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClipRounding {
+    /// Truncates both corners towards zero.
+    #[default]
+    Truncate,
+    /// Floors the top-left and ceils the bottom-right corner, never cutting off a
+    /// partially covered pixel.
+    Outward,
+    /// Rounds both corners to the nearest pixel, ties to even.
+    Nearest,
+}
+
+impl ClipRounding {
+    fn round_min(self, value: f32) -> f32 {
+        match self {
+            Self::Truncate => value.trunc(),
+            Self::Outward => value.floor(),
+            Self::Nearest => value.round_ties_even(),
+        }
+    }
+
+    fn round_max(self, value: f32) -> f32 {
+        match self {
+            Self::Truncate => value.trunc(),
+            Self::Outward => value.ceil(),
+            Self::Nearest => value.round_ties_even(),
+        }
+    }
+}
+
+/// How [`Renderer::draw_background_texture`] sizes a texture to the canvas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackgroundFit {
+    /// Fills the canvas, distorting the texture if the aspect ratios differ.
+    Stretch,
+    /// Shows the whole texture centered, leaving bars where the aspect ratios differ.
+    #[default]
+    Fit,
+    /// Fills the canvas keeping the aspect ratio, cropping what doesn't fit.
+    Cover,
+}
+
+/// Clockwise rotation of the UI on the output, for displays mounted sideways or upside
+/// down. With a quarter turn, imgui's display size has the output's width and height
+/// swapped, and mouse positions have to be rotated back before passing them to imgui.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputRotation {
+    #[default]
+    None,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+/// Returns the largest rect with the aspect ratio of `content_width`x`content_height`
+/// centered in `area`, the way `SDL_RenderSetLogicalSize` letterboxes. Pass it to
+/// [`Renderer::render_in_viewport`] to keep UI anchored to letterboxed content, e.g. an
+/// emulator's 4:3 game view, recomputing it whenever the window is resized.
+pub fn letterbox_rect(area: Rect, content_width: u32, content_height: u32) -> Rect {
+    if content_width == 0 || content_height == 0 {
+        return area;
+    }
+
+    let scale = (area.width() as f32 / content_width as f32)
+        .min(area.height() as f32 / content_height as f32);
+    let width = (content_width as f32 * scale).round() as u32;
+    let height = (content_height as f32 * scale).round() as u32;
+    Rect::new(
+        area.x() + (area.width().saturating_sub(width) / 2) as i32,
+        area.y() + (area.height().saturating_sub(height) / 2) as i32,
+        width,
+        height,
+    )
+}
+
+/// The viewport and scale SDL presents a logical size of `logical` with in an output of
+/// `output` pixels under `SDL_RenderSetIntegerScale`: the largest whole multiple that
+/// fits, but at least 1, centered the way SDL does it. `None` without a logical size.
+fn integer_scale_viewport(output: (u32, u32), logical: (u32, u32)) -> Option<(Rect, u32)> {
+    let (logical_width, logical_height) = logical;
+    if logical_width == 0 || logical_height == 0 {
+        return None;
+    }
+
+    let scale = (output.0 / logical_width)
+        .min(output.1 / logical_height)
+        .max(1);
+    let (width, height) = (logical_width * scale, logical_height * scale);
+    // Like SDL's C arithmetic, this truncates toward zero when the viewport overhangs a
+    // window smaller than the logical size.
+    let x = (output.0 as i32 - width as i32) / 2;
+    let y = (output.1 as i32 - height as i32) / 2;
+    Some((Rect::new(x, y, width, height), scale))
+}
 
 struct BackupSDLRendererState {
+    render_target: *mut SDL_Texture,
+    logical_size: (u32, u32),
+    scale: (f32, f32),
     clip_rect: Option<Rect>,
     viewport: Rect,
+    // Raw, since the host may draw with a custom blend mode `BlendMode` can't hold.
+    blend_mode: u32,
+    draw_color: Color,
+}
+
+/// Canvas changes [`Renderer::reset_render_state`] has to make before drawing the UI.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct RenderStateReset {
+    /// Switching the logical size off, which SDL follows by resetting scale and viewport.
+    clear_logical_size: bool,
+    /// Setting the unit scale, done after clearing the logical size.
+    reset_scale: bool,
+}
+
+impl RenderStateReset {
+    fn for_canvas(logical_size: (u32, u32), scale: (f32, f32)) -> Self {
+        let clear_logical_size = logical_size != (0, 0);
+        Self {
+            clear_logical_size,
+            // Not every SDL release resets the scale along with the logical size.
+            reset_scale: clear_logical_size || scale != (1.0, 1.0),
+        }
+    }
+}
+
+/// Framebuffer size and the transform from imgui's display space to output pixels,
+/// shared by every draw command of a frame.
+///
+/// The UI is drawn with the canvas scale reset to 1, so `framebuffer_scale` is the only
+/// scale applied to it, whatever scale the application uses for its own drawing.
+#[derive(Clone, Copy)]
+struct FrameGeometry {
+    fb_width: f32,
+    fb_height: f32,
+    clip_off: [f32; 2],
+    clip_scale: [f32; 2],
+    // Output position of the UI's top-left corner, for `render_in_viewport`.
+    origin: [f32; 2],
+    rotation: OutputRotation,
+    // Horizontal and vertical mirroring, applied after the rotation.
+    mirror: [bool; 2],
+    snap_to_pixels: bool,
+}
+
+impl FrameGeometry {
+    /// Returns `None` when the frame has nothing to draw into.
+    fn new(
+        display_pos: [f32; 2],
+        display_size: [f32; 2],
+        framebuffer_scale: [f32; 2],
+    ) -> Option<Self> {
+        let fb_height = display_size[1] * framebuffer_scale[1];
+        let fb_width = display_size[0] * framebuffer_scale[0];
+        if !(fb_width > 0.0 && fb_height > 0.0) {
+            return None;
+        }
+
+        Some(Self {
+            fb_width,
+            fb_height,
+            clip_off: display_pos,
+            clip_scale: framebuffer_scale,
+            origin: [0.0, 0.0],
+            rotation: OutputRotation::None,
+            mirror: [false, false],
+            snap_to_pixels: false,
+        })
+    }
+
+    /// Whether vertex positions differ from output pixels and have to be transformed.
+    fn transforms_vertices(&self) -> bool {
+        self.snap_to_pixels
+            || self.clip_off != [0.0, 0.0]
+            || self.clip_scale != [1.0, 1.0]
+            || self.origin != [0.0, 0.0]
+            || self.rotation != OutputRotation::None
+            || self.mirror != [false, false]
+    }
+
+    /// Maps a point in the UI's framebuffer to the output, before the origin offset.
+    fn orient(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let (width, height) = (self.fb_width, self.fb_height);
+        let ([x, y], [out_width, out_height]) = match self.rotation {
+            OutputRotation::None => ([x, y], [width, height]),
+            OutputRotation::Rotate90 => ([height - y, x], [height, width]),
+            OutputRotation::Rotate180 => ([width - x, height - y], [width, height]),
+            OutputRotation::Rotate270 => ([y, width - x], [height, width]),
+        };
+        [
+            if self.mirror[0] { out_width - x } else { x },
+            if self.mirror[1] { out_height - y } else { y },
+        ]
+    }
+
+    fn transform(&self, pos: [f32; 2]) -> [f32; 2] {
+        let [x, y] = self.orient([
+            (pos[0] - self.clip_off[0]) * self.clip_scale[0],
+            (pos[1] - self.clip_off[1]) * self.clip_scale[1],
+        ]);
+        let pos = [x + self.origin[0], y + self.origin[1]];
+        if self.snap_to_pixels {
+            pos.map(f32::round)
+        } else {
+            pos
+        }
+    }
+}
+
+/// What a render call did, so the caller can skip presenting or pause its loop when
+/// nothing was drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[must_use]
+pub enum RenderOutcome {
+    Rendered(RenderStats),
+    Skipped(SkipReason),
+}
+
+impl RenderOutcome {
+    pub fn is_rendered(&self) -> bool {
+        matches!(self, Self::Rendered(_))
+    }
+}
+
+/// Amount of work submitted by one render call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub draw_lists: usize,
+    /// Element commands, each drawn with one or more `SDL_RenderGeometryRaw` calls.
+    pub draw_commands: usize,
+    pub indices: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The display or framebuffer size is zero, e.g. while the window is minimized.
+    EmptyFramebuffer,
+    /// The renderer is suspended; see [`Renderer::suspend`].
+    Suspended,
+}
+
+/// Basic properties of a registered texture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureInfo {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormatEnum,
+    pub access: TextureAccess,
+}
+
+/// Estimated memory held by one registered texture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureMemoryEntry {
+    pub texture_id: imgui::TextureId,
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormatEnum,
+    pub bytes: usize,
+}
+
+/// Estimated memory held by all textures registered with the renderer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextureMemoryUsage {
+    pub total_bytes: usize,
+    pub textures: Vec<TextureMemoryEntry>,
+}
+
+/// Capabilities of the SDL renderer behind a canvas, see [`Renderer::info`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RendererInfo {
+    /// Name of the active render driver, e.g. `"opengl"` or `"software"`.
+    pub driver_name: &'static str,
+    pub software: bool,
+    pub accelerated: bool,
+    pub present_vsync: bool,
+    pub target_texture: bool,
+    pub texture_formats: Vec<PixelFormatEnum>,
+    /// Largest texture size as `(width, height)`; 0 means unknown.
+    pub max_texture_size: (u32, u32),
+}
+
+/// Optional SDL functions available at runtime, see [`Renderer::sdl_features`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SdlFeatures {
+    /// Version of the loaded SDL library.
+    pub version: sdl2::version::Version,
+    /// Per-texture scale modes (SDL 2.0.12).
+    pub texture_scale_modes: bool,
+    /// [`Renderer::update_texture_nv`] (SDL 2.0.16).
+    pub nv_textures: bool,
+    /// [`Renderer::set_vsync`] (SDL 2.0.18).
+    pub vsync_toggle: bool,
+}
+
+/// How a single-channel buffer is expanded to RGBA by
+/// [`Renderer::create_texture_grayscale`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GrayscaleMapping {
+    /// `v` becomes `(v, v, v, 255)`.
+    Replicate,
+    /// `v` becomes `(255, 255, 255, v)`, e.g. for masks and alpha glyphs.
+    WhiteWithAlpha,
+    /// `v` becomes `(v, v, v, v)`.
+    ReplicateWithAlpha,
+}
+
+impl GrayscaleMapping {
+    fn expand(self, value: u8) -> [u8; 4] {
+        match self {
+            GrayscaleMapping::Replicate => [value, value, value, 255],
+            GrayscaleMapping::WhiteWithAlpha => [255, 255, 255, value],
+            GrayscaleMapping::ReplicateWithAlpha => [value; 4],
+        }
+    }
+}
+
+/// Pixel layout used for the font atlas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FontAtlasFormat {
+    /// imgui bakes the atlas as RGBA32, which is uploaded as is.
+    #[default]
+    Rgba32,
+    /// imgui bakes a 1 byte per pixel alpha atlas, cutting its CPU-side copy by 4x.
+    /// It is expanded to white RGBA32 only while uploading.
+    Alpha8,
+    /// Like [`FontAtlasFormat::Alpha8`], but uploaded as 16-bit ARGB4444 to also halve
+    /// GPU memory, at the cost of 16 alpha levels for antialiased edges.
+    Alpha8Argb4444,
+}
+
+/// Settings for building and uploading the font atlas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FontAtlasOptions {
+    pub format: FontAtlasFormat,
+    /// Filtering for the atlas; `Nearest` keeps bitmap fonts crisp at integer scales.
+    pub scale_mode: ScaleMode,
+    /// Uploads the atlas with premultiplied alpha and draws it with a matching blend mode,
+    /// so colored glyphs don't pick up dark fringes at their edges.
+    ///
+    /// Blending itself stays in the framebuffer's nonlinear space, as in Dear ImGui's
+    /// reference backends: `SDL_Renderer` has no sRGB framebuffers or shaders to blend
+    /// in linear space with.
+    pub premultiplied_alpha: bool,
+}
+
+/// CPU-side copy of a texture's pixels, kept so the texture can be recreated after
+/// the GPU copy is lost (e.g. on `SDL_RENDER_DEVICE_RESET`).
+#[derive(Clone, Debug)]
+pub struct TextureSource {
+    pub format: PixelFormatEnum,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    pub options: TextureOptions,
+}
+
+impl TextureSource {
+    /// Mirrors an `SDL_UpdateTexture` upload into the kept pixels: `rect` is clipped to
+    /// the image and read from the start of `pixels`, one row every `pitch` bytes.
+    fn update(
+        &mut self,
+        rect: Option<Rect>,
+        pixels: &[u8],
+        pitch: usize,
+    ) -> Result<(), RendererError> {
+        let full = Rect::new(0, 0, self.width, self.height);
+        let rect = match rect {
+            Some(rect) => match rect.intersection(full) {
+                Some(rect) => rect,
+                None => return Ok(()),
+            },
+            None => full,
+        };
+        let source_pitch = packed_pitch(self.format, self.width)?;
+        let offset = packed_pitch(self.format, rect.x() as u32)?;
+        let row_bytes = packed_pitch(self.format, rect.width())?;
+        let rows = rect.height() as usize;
+        let covered = pitch
+            .checked_mul(rows - 1)
+            .and_then(|bytes| bytes.checked_add(row_bytes))
+            .is_some_and(|bytes| bytes <= pixels.len());
+        if pitch < row_bytes
+            || !covered
+            || self.pixels.len() < source_pitch * rect.bottom() as usize
+        {
+            return Err(RendererError::InvalidPixelData(
+                "pixel data doesn't cover the updated rect".to_string(),
+            ));
+        }
+        for row in 0..rows {
+            let start = (rect.y() as usize + row) * source_pitch + offset;
+            self.pixels[start..start + row_bytes]
+                .copy_from_slice(&pixels[row * pitch..row * pitch + row_bytes]);
+        }
+        Ok(())
+    }
+}
+
+/// A horizontal slice of the font atlas uploaded as its own texture, with its
+/// vertical extent in atlas UV space.
+struct FontBand {
+    texture_id: imgui::TextureId,
+    top: f32,
+    bottom: f32,
+}
+
+struct ScratchTexture {
+    texture_id: imgui::TextureId,
+    width: u32,
+    height: u32,
+    acquired_frame: u64,
+}
+
+/// The texture ids the renderer has handed out. Ids are issued here rather than by
+/// `imgui::Textures`, so they are never reused and an unregistered one stays stale.
+#[derive(Debug)]
+struct TextureIds {
+    registered: BTreeSet<imgui::TextureId>,
+    next: usize,
+}
+
+impl TextureIds {
+    fn new() -> Self {
+        Self {
+            registered: BTreeSet::new(),
+            next: 0,
+        }
+    }
+
+    fn issue(&mut self) -> imgui::TextureId {
+        let texture_id = imgui::TextureId::new(self.next);
+        self.next += 1;
+        self.registered.insert(texture_id);
+        texture_id
+    }
+
+    fn remove(&mut self, texture_id: imgui::TextureId) -> bool {
+        self.registered.remove(&texture_id)
+    }
+
+    fn is_stale(&self, texture_id: imgui::TextureId) -> bool {
+        texture_id.id() < self.next && !self.registered.contains(&texture_id)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = imgui::TextureId> + '_ {
+        self.registered.iter().copied()
+    }
+
+    fn len(&self) -> usize {
+        self.registered.len()
+    }
 }
 
 pub struct Renderer<'a> {
     texture_map: imgui::Textures<Texture<'a>>,
+    texture_ids: TextureIds,
+    retained_sources: HashMap<imgui::TextureId, TextureSource>,
+    labels: HashMap<imgui::TextureId, String>,
+    external_textures: HashSet<imgui::TextureId>,
+    flipped_textures: HashSet<imgui::TextureId>,
+    // Textures holding premultiplied alpha, drawn with premultiplied vertex colors.
+    premultiplied_textures: HashSet<imgui::TextureId>,
+    premultiplied_alpha: bool,
+    default_blend_mode: BlendMode,
+    global_tint: Color,
+    ui_scale: f32,
+    output_rotation: OutputRotation,
+    output_mirror: [bool; 2],
+    flush_before: bool,
+    flush_after: bool,
+    suspended: bool,
+    font_texture_id: imgui::TextureId,
+    font_bands: Vec<FontBand>,
+    // Atlases uploaded for additional contexts through `add_context`.
+    context_fonts: Vec<Vec<FontBand>>,
+    font_options: FontAtlasOptions,
+    max_texture_size: (u32, u32),
+    dpi_scale: f32,
+    scratch_textures: Vec<ScratchTexture>,
+    frame: u64,
+    font_atlas_zoom: Cell<f32>,
+    // Transformed vertex positions, reused across draw commands.
+    positions: RefCell<Vec<[f32; 2]>>,
+    // Vertex colors premultiplied or reordered for SDL, reused across draw commands.
+    colors: RefCell<Vec<SDL_Color>>,
+    // Vertices of each font atlas band, reused across draw commands.
+    font_batches: RefCell<Vec<Vec<SDL_Vertex>>>,
+    missing_texture_policy: MissingTexturePolicy,
+    validate_draw_data: bool,
+    draw_data_issues: RefCell<Vec<DrawDataIssue>>,
+    clip_rounding: ClipRounding,
+    scissor_clipping: bool,
+    pixel_snapping: bool,
+    driver_name: &'static str,
 }
 
 impl<'a> Renderer<'a> {
-    pub fn new(
-        canvas: &'a mut WindowCanvas,
+    pub fn builder() -> RendererBuilder {
+        RendererBuilder::default()
+    }
+
+    /// Creates the renderer and uploads the font atlas. `canvas` is only borrowed for the
+    /// call; the renderer's lifetime comes from `texture_creator` alone.
+    ///
+    /// A renderer can't be attached to a raw `SDL_Renderer` owned by other code (e.g. one
+    /// obtained by hooking a game): `sdl2` only creates canvases and texture creators
+    /// for renderers it creates itself, and SDL allows one renderer per window.
+    pub fn new<T: RenderTarget>(
+        canvas: &mut Canvas<T>,
         imgui_context: &mut imgui::Context,
-        texture_creator: &'a TextureCreator<WindowContext>,
-    ) -> Result<Self, String> {
-        imgui_context
-            .set_renderer_name(format!("imgui-sdl2-renderer {}", env!("CARGO_PKG_VERSION")));
-        imgui_context
-            .io_mut()
-            .backend_flags
-            .insert(imgui::BackendFlags::RENDERER_HAS_VTX_OFFSET);
-
-        let mut fonts = imgui_context.fonts();
-
-        let imgui::FontAtlasTexture {
-            data: pixels,
+        texture_creator: &'a TextureCreator<T::Context>,
+    ) -> Result<Self, RendererError> {
+        Self::with_font_options(
+            canvas,
+            imgui_context,
+            texture_creator,
+            FontAtlasOptions::default(),
+        )
+    }
+
+    /// Like [`Renderer::new`], but controls how the font atlas is built and uploaded.
+    pub fn with_font_options<T: RenderTarget>(
+        canvas: &mut Canvas<T>,
+        imgui_context: &mut imgui::Context,
+        texture_creator: &'a TextureCreator<T::Context>,
+        font_options: FontAtlasOptions,
+    ) -> Result<Self, RendererError> {
+        let font_textures = create_font_textures(imgui_context, texture_creator, font_options)?;
+        Self::from_font_textures(canvas, imgui_context, font_textures, font_options)
+    }
+
+    /// Creates a renderer around a font atlas texture the caller has already baked and uploaded.
+    ///
+    /// The atlas is not rebuilt, so the glyph data in `imgui_context.fonts()` must already
+    /// match `font_texture`. Calling `reload_font_texture` later bakes the atlas as usual.
+    pub fn with_font_texture<T: RenderTarget>(
+        canvas: &mut Canvas<T>,
+        imgui_context: &mut imgui::Context,
+        font_texture: Texture<'a>,
+    ) -> Result<Self, RendererError> {
+        Self::from_font_textures(
+            canvas,
+            imgui_context,
+            vec![(font_texture, 0.0, 1.0)],
+            FontAtlasOptions::default(),
+        )
+    }
+
+    fn from_font_textures<T: RenderTarget>(
+        canvas: &mut Canvas<T>,
+        imgui_context: &mut imgui::Context,
+        font_textures: Vec<(Texture<'a>, f32, f32)>,
+        font_options: FontAtlasOptions,
+    ) -> Result<Self, RendererError> {
+        init_imgui_context(imgui_context);
+
+        let mut font_textures = font_textures.into_iter();
+
+        let mut texture_map = imgui::Textures::new();
+        let mut texture_ids = TextureIds::new();
+
+        let (font_texture, top, bottom) = font_textures.next().ok_or_else(|| {
+            RendererError::TextureCreation("font atlas produced no textures".to_owned())
+        })?;
+        let font_texture_id = texture_ids.issue();
+        texture_map.replace(font_texture_id, font_texture);
+        imgui_context.fonts().tex_id = font_texture_id;
+
+        let mut renderer = Self {
+            texture_map,
+            texture_ids,
+            retained_sources: HashMap::new(),
+            labels: HashMap::from([(font_texture_id, "font atlas".to_owned())]),
+            external_textures: HashSet::new(),
+            flipped_textures: HashSet::new(),
+            premultiplied_textures: HashSet::new(),
+            premultiplied_alpha: false,
+            default_blend_mode: BlendMode::Blend,
+            global_tint: Color::WHITE,
+            ui_scale: 1.0,
+            output_rotation: OutputRotation::None,
+            output_mirror: [false, false],
+            flush_before: false,
+            flush_after: false,
+            suspended: false,
+            font_texture_id,
+            font_bands: vec![FontBand {
+                texture_id: font_texture_id,
+                top,
+                bottom,
+            }],
+            context_fonts: Vec::new(),
+            font_options,
+            max_texture_size: max_texture_size(canvas.raw()),
+            dpi_scale: 1.0,
+            scratch_textures: Vec::new(),
+            frame: 1,
+            font_atlas_zoom: Cell::new(1.0),
+            positions: RefCell::new(Vec::new()),
+            colors: RefCell::new(Vec::new()),
+            font_batches: RefCell::new(Vec::new()),
+            missing_texture_policy: MissingTexturePolicy::default(),
+            validate_draw_data: false,
+            draw_data_issues: RefCell::new(Vec::new()),
+            clip_rounding: ClipRounding::default(),
+            scissor_clipping: true,
+            pixel_snapping: false,
+            driver_name: canvas.info().name,
+        };
+        if font_options.premultiplied_alpha {
+            renderer.premultiplied_textures.insert(font_texture_id);
+        }
+        let mut font_bands = std::mem::take(&mut renderer.font_bands);
+        renderer.push_font_bands(&mut font_bands, font_textures, None);
+        renderer.font_bands = font_bands;
+
+        Ok(renderer)
+    }
+
+    /// Registers a user texture so it can be drawn with `imgui::Image` and friends.
+    pub fn register_texture(&mut self, texture: Texture<'a>) -> imgui::TextureId {
+        let texture_id = self.texture_ids.issue();
+        self.texture_map.replace(texture_id, texture);
+        texture_id
+    }
+
+    /// Registers a texture with a debug label shown by [`Renderer::dump_textures`].
+    pub fn register_texture_labeled(
+        &mut self,
+        texture: Texture<'a>,
+        label: impl Into<String>,
+    ) -> imgui::TextureId {
+        let texture_id = self.register_texture(texture);
+        self.labels.insert(texture_id, label.into());
+        texture_id
+    }
+
+    /// Registers every texture yielded by `textures`, returning their ids in order.
+    pub fn register_textures<I>(&mut self, textures: I) -> Vec<imgui::TextureId>
+    where
+        I: IntoIterator<Item = Texture<'a>>,
+    {
+        textures
+            .into_iter()
+            .map(|texture| self.register_texture(texture))
+            .collect()
+    }
+
+    /// Applies `options` to the texture, then registers it.
+    pub fn register_texture_with_options(
+        &mut self,
+        mut texture: Texture<'a>,
+        options: TextureOptions,
+    ) -> Result<imgui::TextureId, RendererError> {
+        apply_texture_options(&mut texture, options)?;
+        Ok(self.register_texture(texture))
+    }
+
+    /// Registers a texture holding many sprites and returns an atlas to name regions of it.
+    pub fn register_atlas(&mut self, texture: Texture<'a>) -> TextureAtlas {
+        let query = texture.query();
+        TextureAtlas::new(self.register_texture(texture), query.width, query.height)
+    }
+
+    /// Swaps the texture behind an existing id, returning the previous texture. Ids
+    /// already stored in UI state and draw lists keep working. The new texture starts
+    /// out unmarked; call [`Renderer::set_texture_premultiplied`] again if it holds
+    /// premultiplied alpha.
+    pub fn replace_texture(
+        &mut self,
+        texture_id: imgui::TextureId,
+        texture: Texture<'a>,
+    ) -> Result<Texture<'a>, RendererError> {
+        if self.external_textures.contains(&texture_id) {
+            return Err(RendererError::ExternalTexture(texture_id));
+        }
+
+        let previous = std::mem::replace(self.texture_mut(texture_id)?, texture);
+        self.retained_sources.remove(&texture_id);
+        self.premultiplied_textures.remove(&texture_id);
+        Ok(previous)
+    }
+
+    /// Removes a texture from the renderer, handing ownership back to the caller.
+    /// Textures registered with [`Renderer::register_raw_texture`] are released
+    /// without being destroyed and `None` is returned for them.
+    pub fn unregister_texture(&mut self, texture_id: imgui::TextureId) -> Option<Texture<'a>> {
+        self.texture_ids.remove(texture_id);
+        self.retained_sources.remove(&texture_id);
+        self.labels.remove(&texture_id);
+        self.flipped_textures.remove(&texture_id);
+        self.premultiplied_textures.remove(&texture_id);
+
+        let texture = self.texture_map.remove(texture_id)?;
+        if self.external_textures.remove(&texture_id) {
+            std::mem::forget(texture);
+            return None;
+        }
+        Some(texture)
+    }
+
+    /// Registers an `SDL_Texture` owned by foreign code. The renderer never destroys
+    /// it; unregister it (or drop the renderer) before the owner frees it.
+    ///
+    /// # Safety
+    /// `raw` must be a valid texture created by the same `SDL_Renderer` as
+    /// `texture_creator`, and must outlive its registration.
+    pub unsafe fn register_raw_texture<T>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+        raw: *mut SDL_Texture,
+    ) -> imgui::TextureId {
+        let texture_id = self.register_texture(texture_creator.raw_create_texture(raw));
+        self.external_textures.insert(texture_id);
+        texture_id
+    }
+
+    /// Uploads tightly packed RGBA32 pixels into a new texture and registers it.
+    pub fn create_texture_rgba<T>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<imgui::TextureId, RendererError> {
+        if !self.premultiplied_alpha {
+            return self.create_texture(
+                texture_creator,
+                PixelFormatEnum::RGBA32,
+                width,
+                height,
+                pixels,
+            );
+        }
+
+        let premultiplied: Vec<u8> = pixels
+            .chunks_exact(4)
+            .flat_map(|pixel| {
+                let alpha = pixel[3];
+                [
+                    premultiply(pixel[0], alpha),
+                    premultiply(pixel[1], alpha),
+                    premultiply(pixel[2], alpha),
+                    alpha,
+                ]
+            })
+            .collect();
+        let texture_id = self.create_texture(
+            texture_creator,
+            PixelFormatEnum::RGBA32,
+            width,
             height,
+            &premultiplied,
+        )?;
+        self.set_texture_premultiplied(texture_id, true)?;
+        Ok(texture_id)
+    }
+
+    /// Expands a single-channel buffer (heightmaps, masks, ...) to RGBA32 according
+    /// to `mapping` and registers it as a texture.
+    pub fn create_texture_grayscale<T>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        mapping: GrayscaleMapping,
+    ) -> Result<imgui::TextureId, RendererError> {
+        let count = width as usize * height as usize;
+        if pixels.len() < count {
+            return Err(RendererError::InvalidPixelData(format!(
+                "expected {} bytes of grayscale pixel data for a {}x{} texture, got {}",
+                count,
+                width,
+                height,
+                pixels.len()
+            )));
+        }
+
+        let rgba: Vec<u8> = pixels[..count]
+            .iter()
+            .flat_map(|&value| mapping.expand(value))
+            .collect();
+
+        self.create_texture_rgba(texture_creator, width, height, &rgba)
+    }
+
+    /// Uploads tightly packed pixels of any packed `format` (e.g. BGRA32) into a new
+    /// texture and registers it. Planar YUV and sub-byte indexed formats fail with
+    /// [`RendererError::InvalidPixelData`]; create planar textures with
+    /// [`Renderer::create_streaming_texture`] and fill them with
+    /// [`Renderer::update_texture_yuv`] or [`Renderer::update_texture_nv`].
+    pub fn create_texture<T>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+        format: PixelFormatEnum,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<imgui::TextureId, RendererError> {
+        let texture = create_static_texture(
+            texture_creator,
+            format,
             width,
-        } = fonts.build_rgba32_texture();
+            height,
+            pixels,
+            self.texture_options(),
+        )?;
+        Ok(self.register_texture(texture))
+    }
 
-        let mut font_texture = texture_creator
-            .create_texture_static(PixelFormatEnum::RGBA32, width, height)
-            .map_err(|error| error.to_string())?;
+    /// Converts a surface to RGBA32, uploads it into a new texture and registers it.
+    pub fn register_surface<T>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+        surface: &SurfaceRef,
+    ) -> Result<imgui::TextureId, RendererError> {
+        let texture = create_surface_texture(texture_creator, surface, self.texture_options())?;
+        Ok(self.register_texture(texture))
+    }
 
-        font_texture
-            .update(None, pixels, (width * RGBA32_BYTES) as _)
-            .map_err(|error| error.to_string())?;
-        canvas.set_blend_mode(BlendMode::Blend);
-        font_texture.set_blend_mode(BlendMode::Blend);
+    /// Decodes an image file (PNG, JPEG or BMP) and registers it as an RGBA32 texture.
+    #[cfg(feature = "image")]
+    pub fn load_texture_file<T, P: AsRef<std::path::Path>>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+        path: P,
+    ) -> Result<imgui::TextureId, RendererError> {
+        let image = image::open(path)?.into_rgba8();
+        let (width, height) = image.dimensions();
 
-        unsafe {
-            SDL_SetTextureScaleMode(font_texture.raw(), SDL_ScaleMode::SDL_ScaleModeLinear);
+        self.create_texture_rgba(texture_creator, width, height, image.as_raw())
+    }
+
+    /// Uploads new pixel data into the whole texture, or only into `rect` when given.
+    /// A retained texture's source is updated to match; if the upload can't be mirrored
+    /// into it, the source is dropped rather than left stale.
+    pub fn update_texture(
+        &mut self,
+        texture_id: imgui::TextureId,
+        rect: Option<Rect>,
+        pixels: &[u8],
+        pitch: usize,
+    ) -> Result<(), RendererError> {
+        self.texture_mut(texture_id)?
+            .update(rect, pixels, pitch)
+            .map_err(|error| RendererError::TextureUpload(error.to_string()))?;
+        if let Some(source) = self.retained_sources.get_mut(&texture_id) {
+            if source.update(rect, pixels, pitch).is_err() {
+                self.retained_sources.remove(&texture_id);
+            }
         }
+        Ok(())
+    }
 
-        let mut texture_map = imgui::Textures::new();
+    /// Creates and registers a streaming texture meant to be rewritten every frame
+    /// through [`Renderer::with_texture_lock`].
+    pub fn create_streaming_texture<T>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+        format: PixelFormatEnum,
+        width: u32,
+        height: u32,
+    ) -> Result<imgui::TextureId, RendererError> {
+        check_texture_size(texture_creator, width, height)?;
+        let mut texture = texture_creator
+            .create_texture_streaming(format, width, height)
+            .map_err(|error| RendererError::TextureCreation(error.to_string()))?;
+        apply_texture_options(&mut texture, self.texture_options())?;
+
+        Ok(self.register_texture(texture))
+    }
 
-        fonts.tex_id = texture_map.insert(font_texture);
+    /// Creates and registers a texture that can be rendered into with
+    /// [`Renderer::render_to_texture`].
+    pub fn create_target_texture<T>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+        width: u32,
+        height: u32,
+    ) -> Result<imgui::TextureId, RendererError> {
+        check_texture_size(texture_creator, width, height)?;
+        let mut texture = texture_creator
+            .create_texture_target(PixelFormatEnum::RGBA32, width, height)
+            .map_err(|error| RendererError::TextureCreation(error.to_string()))?;
+        apply_texture_options(&mut texture, self.texture_options())?;
 
-        Ok(Self { texture_map })
+        Ok(self.register_texture(texture))
     }
 
-    pub fn render(
-        &self,
-        canvas: &'a mut WindowCanvas,
-        draw_data: &imgui::DrawData,
-    ) -> Result<(), String> {
-        let (rsx, rsy) = canvas.scale();
-        let render_scale = [
-            if rsx == 1.0 {
-                draw_data.framebuffer_scale[0]
-            } else {
-                1.0
-            },
-            if rsy == 1.0 {
-                draw_data.framebuffer_scale[1]
-            } else {
-                1.0
-            },
-        ];
+    /// Starts a new frame: scratch textures acquired during the previous one go back to
+    /// the pool and [`TextureCache`] entries it used become evictable again. Call it once
+    /// per frame before building the UI, however many render calls the frame takes.
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+        self.draw_data_issues.get_mut().clear();
+    }
 
-        let fb_height = draw_data.display_size[1] * render_scale[1];
-        let fb_width = draw_data.display_size[0] * render_scale[0];
-        if !(fb_width > 0.0 && fb_height > 0.0) {
-            return Ok(());
+    /// Returns a streaming RGBA32 texture of the given size that is only valid for the
+    /// current frame; its contents should be written with
+    /// [`Renderer::with_texture_lock`] before use. The texture goes back to the pool,
+    /// and may be handed out again, on the next [`Renderer::begin_frame`].
+    pub fn acquire_scratch<T>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+        width: u32,
+        height: u32,
+    ) -> Result<imgui::TextureId, RendererError> {
+        let frame = self.frame;
+        let texture_map = &self.texture_map;
+        self.scratch_textures
+            .retain(|scratch| texture_map.get(scratch.texture_id).is_some());
+
+        if let Some(scratch) = self.scratch_textures.iter_mut().find(|scratch| {
+            scratch.acquired_frame < frame && scratch.width == width && scratch.height == height
+        }) {
+            scratch.acquired_frame = frame;
+            return Ok(scratch.texture_id);
+        }
+
+        let texture_id =
+            self.create_streaming_texture(texture_creator, PixelFormatEnum::RGBA32, width, height)?;
+        self.scratch_textures.push(ScratchTexture {
+            texture_id,
+            width,
+            height,
+            acquired_frame: frame,
+        });
+        Ok(texture_id)
+    }
+
+    /// Locks a streaming texture (or a `rect` of it) and passes its pixel buffer and
+    /// pitch to `write`. Writes through a lock aren't tracked, so a retained source for
+    /// the texture is dropped.
+    pub fn with_texture_lock<F, R>(
+        &mut self,
+        texture_id: imgui::TextureId,
+        rect: Option<Rect>,
+        write: F,
+    ) -> Result<R, RendererError>
+    where
+        F: FnOnce(&mut [u8], usize) -> R,
+    {
+        let result = self
+            .texture_mut(texture_id)?
+            .with_lock(rect, write)
+            .map_err(RendererError::TextureUpload)?;
+        self.retained_sources.remove(&texture_id);
+        Ok(result)
+    }
+
+    /// Uploads planar IYUV/YV12 data into a registered YUV texture.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_texture_yuv(
+        &mut self,
+        texture_id: imgui::TextureId,
+        rect: Option<Rect>,
+        y_plane: &[u8],
+        y_pitch: usize,
+        u_plane: &[u8],
+        u_pitch: usize,
+        v_plane: &[u8],
+        v_pitch: usize,
+    ) -> Result<(), RendererError> {
+        self.texture_mut(texture_id)?
+            .update_yuv(rect, y_plane, y_pitch, u_plane, u_pitch, v_plane, v_pitch)
+            .map_err(|error| RendererError::TextureUpload(error.to_string()))
+    }
+
+    /// Uploads NV12/NV21 data (a Y plane followed by an interleaved UV plane) into a
+    /// registered texture.
+    pub fn update_texture_nv(
+        &mut self,
+        texture_id: imgui::TextureId,
+        rect: Option<Rect>,
+        y_plane: &[u8],
+        y_pitch: usize,
+        uv_plane: &[u8],
+        uv_pitch: usize,
+    ) -> Result<(), RendererError> {
+        let texture = self.texture_mut(texture_id)?;
+        let height = match rect {
+            Some(rect) => rect.height(),
+            None => texture.query().height,
+        } as usize;
+
+        if y_plane.len() < y_pitch * height || uv_plane.len() < uv_pitch * height.div_ceil(2) {
+            return Err(RendererError::InvalidPixelData(
+                "NV plane data is smaller than the region being updated".to_owned(),
+            ));
         }
 
-        let backup = BackupSDLRendererState {
-            clip_rect: canvas.clip_rect(),
-            viewport: canvas.viewport(),
+        let update_nv_texture = dynamic::optional_functions()
+            .update_nv_texture
+            .ok_or_else(|| unsupported_sdl_version(16))?;
+        let result = unsafe {
+            update_nv_texture(
+                texture.raw(),
+                match rect {
+                    Some(ref rect) => rect.raw(),
+                    None => null(),
+                },
+                y_plane.as_ptr(),
+                y_pitch as _,
+                uv_plane.as_ptr(),
+                uv_pitch as _,
+            )
         };
 
-        let clip_off = draw_data.display_pos;
-        let clip_scale = render_scale;
-
-        for draw_list in draw_data.draw_lists() {
-            let idx_buffer: &[imgui::DrawIdx] = draw_list.idx_buffer();
-            let vtx_buffer = draw_list.vtx_buffer();
-
-            for command in draw_list.commands() {
-                match command {
-                    imgui::DrawCmd::Elements { count, cmd_params } => {
-                        let mut clip_min = [
-                            (cmd_params.clip_rect[0] - clip_off[0]) * clip_scale[0],
-                            (cmd_params.clip_rect[1] - clip_off[1]) * clip_scale[1],
-                        ];
-                        let mut clip_max = [
-                            (cmd_params.clip_rect[2] - clip_off[0]) * clip_scale[0],
-                            (cmd_params.clip_rect[3] - clip_off[1]) * clip_scale[1],
-                        ];
-
-                        if clip_min[0] < 0.0 {
-                            clip_min[0] = 0.0;
-                        }
-                        if clip_min[1] < 0.0 {
-                            clip_min[1] = 0.0;
-                        }
-                        if clip_max[0] > fb_width {
-                            clip_max[0] = fb_width;
-                        }
-                        if clip_max[1] > fb_height {
-                            clip_max[1] = fb_height;
-                        }
-                        if clip_max[0] <= clip_min[0] || clip_max[1] <= clip_min[1] {
-                            continue;
-                        }
+        if result != 0 {
+            return Err(sdl_call_error("SDL_UpdateNVTexture"));
+        }
+        Ok(())
+    }
 
-                        unsafe {
-                            let rect = Rect::new(
-                                clip_min[0] as _,
-                                clip_min[1] as _,
-                                (clip_max[0] - clip_min[0]) as u32,
-                                (clip_max[1] - clip_min[1]) as u32,
-                            );
-                            canvas.set_clip_rect(rect);
-
-                            let vtx_buffer_ptr = vtx_buffer.as_ptr();
-                            let idx_buffer_ptr = idx_buffer.as_ptr();
-
-                            let position_field_offset = (vtx_buffer_ptr.add(cmd_params.vtx_offset)
-                                as usize)
-                                + memoffset::offset_of!(imgui::DrawVert, pos);
-                            let uv_field_offset = (vtx_buffer_ptr.add(cmd_params.vtx_offset)
-                                as usize)
-                                + memoffset::offset_of!(imgui::DrawVert, uv);
-                            let color_field_offset = (vtx_buffer_ptr.add(cmd_params.vtx_offset)
-                                as usize)
-                                + memoffset::offset_of!(imgui::DrawVert, col);
-
-                            let font_texture = self.texture_map.get(cmd_params.texture_id);
-
-                            SDL_RenderGeometryRaw(
-                                canvas.raw(),
-                                match font_texture {
-                                    Some(texture) => texture.raw(),
-                                    None => null_mut(),
-                                },
-                                position_field_offset as *const f32,
-                                size_of::<imgui::DrawVert>() as _,
-                                color_field_offset as *const SDL_Color,
-                                size_of::<imgui::DrawVert>() as _,
-                                uv_field_offset as *const f32,
-                                size_of::<imgui::DrawVert>() as _,
-                                (vtx_buffer.len() - cmd_params.vtx_offset) as _,
-                                idx_buffer_ptr.add(cmd_params.idx_offset).cast(),
-                                count as _,
-                                size_of::<imgui::DrawIdx>() as _,
-                            );
-                        }
-                    }
-                    imgui::DrawCmd::RawCallback { callback, raw_cmd } => unsafe {
-                        callback(draw_list.raw(), raw_cmd)
-                    },
-                    imgui::DrawCmd::ResetRenderState => Self::setup_render_state(canvas),
-                }
-            }
+    pub fn set_texture_scale_mode(
+        &mut self,
+        texture_id: imgui::TextureId,
+        scale_mode: ScaleMode,
+    ) -> Result<(), RendererError> {
+        set_scale_mode(self.texture_mut(texture_id)?, scale_mode)?;
+        if let Some(source) = self.retained_sources.get_mut(&texture_id) {
+            source.options.scale_mode = scale_mode;
+        }
+        Ok(())
+    }
+
+    pub fn set_texture_blend_mode(
+        &mut self,
+        texture_id: imgui::TextureId,
+        blend_mode: BlendMode,
+    ) -> Result<(), RendererError> {
+        self.texture_mut(texture_id)?.set_blend_mode(blend_mode);
+        self.premultiplied_textures.remove(&texture_id);
+        if let Some(source) = self.retained_sources.get_mut(&texture_id) {
+            source.options.blend_mode = blend_mode;
         }
+        Ok(())
+    }
 
-        canvas.set_clip_rect(backup.clip_rect);
-        canvas.set_viewport(backup.viewport);
+    /// Marks a texture as holding premultiplied alpha: it is blended with
+    /// `ONE, ONE_MINUS_SRC_ALPHA` and drawn with premultiplied vertex colors. Unmarking
+    /// it switches it back to [`BlendMode::Blend`].
+    pub fn set_texture_premultiplied(
+        &mut self,
+        texture_id: imgui::TextureId,
+        premultiplied: bool,
+    ) -> Result<(), RendererError> {
+        let texture = self.texture_mut(texture_id)?;
+        if premultiplied {
+            set_premultiplied_blend_mode(texture)?;
+            self.premultiplied_textures.insert(texture_id);
+        } else {
+            texture.set_blend_mode(BlendMode::Blend);
+            self.premultiplied_textures.remove(&texture_id);
+            if let Some(source) = self.retained_sources.get_mut(&texture_id) {
+                source.options.blend_mode = BlendMode::Blend;
+            }
+        }
         Ok(())
     }
 
-    pub fn setup_render_state(canvas: &mut WindowCanvas) {
-        canvas.set_clip_rect(None);
-        canvas.set_viewport(None);
+    pub fn texture_premultiplied(&self, texture_id: imgui::TextureId) -> bool {
+        self.premultiplied_textures.contains(&texture_id)
+    }
+
+    /// Premultiplies the pixels of textures created afterwards with
+    /// [`Renderer::create_texture_rgba`] or [`Renderer::create_texture_grayscale`] and
+    /// marks them with [`Renderer::set_texture_premultiplied`]. The font atlas is
+    /// configured separately through [`FontAtlasOptions::premultiplied_alpha`].
+    pub fn set_premultiplied_alpha(&mut self, premultiplied: bool) {
+        self.premultiplied_alpha = premultiplied;
+    }
+
+    pub fn premultiplied_alpha(&self) -> bool {
+        self.premultiplied_alpha
+    }
+
+    /// Blend mode of textures created afterwards through the renderer's helpers
+    /// ([`Renderer::create_texture`], [`Renderer::register_surface`], streaming and target
+    /// textures, ...); [`BlendMode::Blend`] by default. Premultiplied textures still get
+    /// the premultiplied blend mode, and the font atlas is unaffected.
+    pub fn set_default_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.default_blend_mode = blend_mode;
+    }
+
+    pub fn default_blend_mode(&self) -> BlendMode {
+        self.default_blend_mode
+    }
+
+    fn texture_options(&self) -> TextureOptions {
+        TextureOptions {
+            blend_mode: self.default_blend_mode,
+            ..TextureOptions::default()
+        }
+    }
+
+    /// Multiplies the color of everything the renderer draws with `tint`, e.g. to dim
+    /// the whole UI behind a modal overlay. White leaves colors unchanged.
+    pub fn set_global_tint(&mut self, tint: Color) {
+        self.global_tint = tint;
+    }
+
+    pub fn global_tint(&self) -> Color {
+        self.global_tint
+    }
+
+    /// Sets the alpha of the global tint, from 0 (invisible) to 1, to fade the whole UI
+    /// in or out.
+    pub fn set_global_alpha(&mut self, alpha: f32) {
+        self.global_tint.a = (alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+
+    pub fn global_alpha(&self) -> f32 {
+        self.global_tint.a as f32 / 255.0
+    }
+
+    /// Zooms the whole UI by `scale` when drawing it, without rebuilding the font atlas;
+    /// glyphs are magnified by texture filtering. Mouse positions reported to imgui have
+    /// to be divided by the same factor. Non-positive or non-finite values reset it to 1.
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.ui_scale = if scale.is_finite() && scale > 0.0 {
+            scale
+        } else {
+            1.0
+        };
+    }
+
+    pub fn ui_scale(&self) -> f32 {
+        self.ui_scale
+    }
+
+    pub fn set_output_rotation(&mut self, rotation: OutputRotation) {
+        self.output_rotation = rotation;
+    }
+
+    pub fn output_rotation(&self) -> OutputRotation {
+        self.output_rotation
+    }
+
+    /// Mirrors the whole UI on the output, e.g. for rear projection or teleprompters.
+    /// Applied after [`Renderer::set_output_rotation`]; mouse positions have to be
+    /// mirrored back before passing them to imgui.
+    pub fn set_output_mirroring(&mut self, horizontal: bool, vertical: bool) {
+        self.output_mirror = [horizontal, vertical];
+    }
+
+    /// Horizontal and vertical mirroring, in that order.
+    pub fn output_mirroring(&self) -> (bool, bool) {
+        (self.output_mirror[0], self.output_mirror[1])
+    }
+
+    /// Calls `SDL_RenderFlush` before and/or after drawing the UI, for applications that
+    /// mix raw OpenGL (or other graphics API) calls with `SDL_Renderer`: SDL batches its
+    /// drawing, so pending commands have to be flushed before touching the API directly.
+    pub fn set_render_flush(&mut self, before: bool, after: bool) {
+        self.flush_before = before;
+        self.flush_after = after;
+    }
+
+    /// Whether the UI is flushed before and after drawing, in that order.
+    pub fn render_flush(&self) -> (bool, bool) {
+        (self.flush_before, self.flush_after)
+    }
+
+    /// Reports the estimated memory used by registered textures.
+    pub fn texture_memory_usage(&self) -> TextureMemoryUsage {
+        let textures: Vec<_> = self
+            .texture_ids
+            .iter()
+            .filter_map(|texture_id| {
+                let texture = self.texture_map.get(texture_id)?;
+                let query = texture.query();
+                Some(TextureMemoryEntry {
+                    texture_id,
+                    width: query.width,
+                    height: query.height,
+                    format: query.format,
+                    bytes: texture_size_bytes(texture),
+                })
+            })
+            .collect();
+
+        TextureMemoryUsage {
+            total_bytes: textures.iter().map(|entry| entry.bytes).sum(),
+            textures,
+        }
+    }
+
+    /// Creates and registers a texture from `source`, keeping the source around so
+    /// [`Renderer::recreate_textures`] can restore it later. [`Renderer::update_texture`]
+    /// keeps the source in sync; other ways of changing the pixels drop it, and the
+    /// texture is then no longer restored.
+    pub fn create_retained_texture<T>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+        source: TextureSource,
+    ) -> Result<imgui::TextureId, RendererError> {
+        let texture = create_source_texture(texture_creator, &source)?;
+        let texture_id = self.register_texture(texture);
+        self.retained_sources.insert(texture_id, source);
+        Ok(texture_id)
+    }
+
+    /// Rebuilds the font atlas of `imgui_context` (the one the renderer was created with,
+    /// or one added with [`Renderer::add_context`]) and uploads it again, e.g. after fonts
+    /// were added or glyph ranges changed. The font texture keeps its id.
+    pub fn reload_font_texture<T>(
+        &mut self,
+        imgui_context: &mut imgui::Context,
+        texture_creator: &'a TextureCreator<T>,
+    ) -> Result<(), RendererError> {
+        let mut font_textures =
+            create_font_textures(imgui_context, texture_creator, self.font_options)?.into_iter();
+        let (font_texture, top, bottom) = font_textures.next().ok_or_else(|| {
+            RendererError::TextureCreation("font atlas produced no textures".to_owned())
+        })?;
+
+        let context = self.context_index(imgui_context.fonts().tex_id);
+        let mut bands = match context {
+            Some(index) => std::mem::take(&mut self.context_fonts[index]),
+            None => std::mem::take(&mut self.font_bands),
+        };
+
+        for band in bands.drain(1..) {
+            self.unregister_texture(band.texture_id);
+        }
+        self.texture_map.replace(bands[0].texture_id, font_texture);
+        if self.font_options.premultiplied_alpha {
+            self.premultiplied_textures.insert(bands[0].texture_id);
+        }
+        bands[0].top = top;
+        bands[0].bottom = bottom;
+        self.push_font_bands(&mut bands, font_textures, context);
+
+        imgui_context.fonts().tex_id = bands[0].texture_id;
+        match context {
+            Some(index) => self.context_fonts[index] = bands,
+            None => self.font_bands = bands,
+        }
+        Ok(())
+    }
+
+    /// Uploads the font atlas of another imgui context (e.g. an in-game debug UI next to
+    /// the tool UI) so its draw data can be rendered by this renderer too, and marks the
+    /// context as rendered by it. Returns the id assigned to that atlas. Contexts sharing
+    /// one `imgui::SharedFontAtlas` only need the atlas uploaded once.
+    pub fn add_context<T>(
+        &mut self,
+        imgui_context: &mut imgui::Context,
+        texture_creator: &'a TextureCreator<T>,
+    ) -> Result<imgui::TextureId, RendererError> {
+        let font_textures =
+            create_font_textures(imgui_context, texture_creator, self.font_options)?;
+        if font_textures.is_empty() {
+            return Err(RendererError::TextureCreation(
+                "font atlas produced no textures".to_owned(),
+            ));
+        }
+
+        let context = self.context_fonts.len();
+        let mut bands = Vec::new();
+        self.push_font_bands(&mut bands, font_textures.into_iter(), Some(context));
+
+        init_imgui_context(imgui_context);
+        imgui_context.fonts().tex_id = bands[0].texture_id;
+        self.context_fonts.push(bands);
+        Ok(imgui_context.fonts().tex_id)
+    }
+
+    /// Drops the font atlas uploaded for `imgui_context` by [`Renderer::add_context`]
+    /// and undoes the renderer state set on the context.
+    pub fn remove_context(&mut self, imgui_context: &mut imgui::Context) {
+        if let Some(index) = self.context_index(imgui_context.fonts().tex_id) {
+            for band in self.context_fonts.remove(index) {
+                self.unregister_texture(band.texture_id);
+            }
+            reset_imgui_context(imgui_context);
+        }
+    }
+
+    fn context_index(&self, font_texture_id: imgui::TextureId) -> Option<usize> {
+        self.context_fonts
+            .iter()
+            .position(|bands| bands[0].texture_id == font_texture_id)
+    }
+
+    /// Registers font atlas bands and appends them to `bands`; `context` is `None` for
+    /// the renderer's own context.
+    fn push_font_bands<I>(
+        &mut self,
+        bands: &mut Vec<FontBand>,
+        font_textures: I,
+        context: Option<usize>,
+    ) where
+        I: Iterator<Item = (Texture<'a>, f32, f32)>,
+    {
+        for (texture, top, bottom) in font_textures {
+            let label = match (context, bands.len()) {
+                (None, 0) => "font atlas".to_owned(),
+                (None, band) => format!("font atlas (band {})", band),
+                (Some(context), 0) => format!("font atlas (context {})", context + 1),
+                (Some(context), band) => {
+                    format!("font atlas (context {}, band {})", context + 1, band)
+                }
+            };
+            let texture_id = self.register_texture_labeled(texture, label);
+            if self.font_options.premultiplied_alpha {
+                self.premultiplied_textures.insert(texture_id);
+            }
+            bands.push(FontBand {
+                texture_id,
+                top,
+                bottom,
+            });
+        }
+    }
+
+    /// Band lists of every uploaded font atlas, the renderer's own first.
+    fn font_band_sets(&self) -> impl Iterator<Item = &[FontBand]> {
+        std::iter::once(self.font_bands.as_slice())
+            .chain(self.context_fonts.iter().map(Vec::as_slice))
+    }
+
+    fn is_font_texture(&self, texture_id: imgui::TextureId) -> bool {
+        self.font_band_sets()
+            .flatten()
+            .any(|band| band.texture_id == texture_id)
+    }
+
+    /// Checks the window's pixel density and, when it changed since the last call,
+    /// rebuilds the fonts for the new scale through [`Renderer::set_dpi_scale`]. Call
+    /// this once per frame (or on window events) to keep text crisp when the window
+    /// moves between monitors. Returns whether the atlas was rebuilt.
+    pub fn sync_dpi_scale<F>(
+        &mut self,
+        canvas: &WindowCanvas,
+        imgui_context: &mut imgui::Context,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        rebuild_fonts: F,
+    ) -> Result<bool, RendererError>
+    where
+        F: FnOnce(&mut imgui::FontAtlas, f32),
+    {
+        let (output_width, _) = canvas.output_size()?;
+        let (window_width, _) = canvas.window().size();
+        if window_width == 0 {
+            return Ok(false);
+        }
+
+        self.set_dpi_scale(
+            imgui_context,
+            texture_creator,
+            output_width as f32 / window_width as f32,
+            rebuild_fonts,
+        )
+    }
+
+    /// Rebuilds the font atlas for `scale` when it differs from the current one.
+    /// `rebuild_fonts` receives the cleared atlas and the new scale and should add the
+    /// application's fonts at `size * scale`. The global font scale and framebuffer
+    /// scale are adjusted so the UI keeps its logical size.
+    pub fn set_dpi_scale<T, F>(
+        &mut self,
+        imgui_context: &mut imgui::Context,
+        texture_creator: &'a TextureCreator<T>,
+        scale: f32,
+        rebuild_fonts: F,
+    ) -> Result<bool, RendererError>
+    where
+        F: FnOnce(&mut imgui::FontAtlas, f32),
+    {
+        if !scale.is_finite() || scale <= 0.0 || scale == self.dpi_scale {
+            return Ok(false);
+        }
+
+        {
+            let mut fonts = imgui_context.fonts();
+            fonts.clear();
+            rebuild_fonts(&mut fonts, scale);
+        }
+
+        let io = imgui_context.io_mut();
+        io.font_global_scale = 1.0 / scale;
+        io.display_framebuffer_scale = [scale, scale];
+
+        self.reload_font_texture(imgui_context, texture_creator)?;
+        self.dpi_scale = scale;
+        Ok(true)
+    }
+
+    /// Largest texture the renderer accepts, as `(width, height)`; 0 means unknown.
+    pub fn max_texture_size(&self) -> (u32, u32) {
+        self.max_texture_size
+    }
+
+    pub fn dpi_scale(&self) -> f32 {
+        self.dpi_scale
+    }
+
+    /// Shows a window drawing the font atlas as uploaded by this renderer, one image per band,
+    /// with a zoom slider for inspecting glyph packing and filtering.
+    pub fn show_font_atlas_window(&self, ui: &imgui::Ui) {
+        let mut zoom = self.font_atlas_zoom.get();
+        imgui::Window::new("Font atlas")
+            .horizontal_scrollbar(true)
+            .build(ui, || {
+                imgui::Slider::new("Zoom", 0.25, 8.0).build(ui, &mut zoom);
+                for band in &self.font_bands {
+                    if let Some(texture) = self.texture_map.get(band.texture_id) {
+                        let query = texture.query();
+                        ui.text(format!(
+                            "{:?}: {}x{} {:?}",
+                            band.texture_id, query.width, query.height, query.format
+                        ));
+                        imgui::Image::new(
+                            band.texture_id,
+                            [query.width as f32 * zoom, query.height as f32 * zoom],
+                        )
+                        .build(ui);
+                    }
+                }
+            });
+        self.font_atlas_zoom.set(zoom);
+    }
+
+    pub fn set_missing_texture_policy(&mut self, policy: MissingTexturePolicy) {
+        self.missing_texture_policy = policy;
+    }
+
+    pub fn missing_texture_policy(&self) -> MissingTexturePolicy {
+        self.missing_texture_policy
+    }
+
+    /// Runs the checks of [`validate_draw_data`] on every command before drawing it. Issues
+    /// that mean the command can't be drawn (see [`DrawDataIssueKind::is_fatal`]) fail
+    /// rendering with [`RendererError::InvalidDrawData`]; the others are collected in
+    /// [`Renderer::draw_data_issues`]. Off by default, as it walks every vertex.
+    pub fn set_validate_draw_data(&mut self, validate: bool) {
+        self.validate_draw_data = validate;
+    }
+
+    pub fn validates_draw_data(&self) -> bool {
+        self.validate_draw_data
+    }
+
+    /// Non-fatal issues found by draw data validation since the last
+    /// [`Renderer::begin_frame`], e.g. empty clip rects, which Dear ImGui legitimately
+    /// emits for tiny windows.
+    pub fn draw_data_issues(&self) -> Vec<DrawDataIssue> {
+        self.draw_data_issues.borrow().clone()
+    }
+
+    pub fn set_clip_rounding(&mut self, rounding: ClipRounding) {
+        self.clip_rounding = rounding;
+    }
+
+    pub fn clip_rounding(&self) -> ClipRounding {
+        self.clip_rounding
+    }
+
+    /// Turning this off stops the renderer from calling `SDL_RenderSetClipRect`, for
+    /// drivers that mishandle clip rects or to find out whether a rendering bug comes
+    /// from clipping. Commands whose clip rect is empty are still skipped, but the rest
+    /// overdraw: scrolled-out content shows outside its window, and frames drawn with
+    /// [`Renderer::render_in_viewport`] aren't confined to the viewport. Takes effect
+    /// from the next command on, so it can be toggled between frames.
+    pub fn set_scissor_clipping(&mut self, enabled: bool) {
+        self.scissor_clipping = enabled;
+    }
+
+    pub fn scissor_clipping(&self) -> bool {
+        self.scissor_clipping
+    }
+
+    /// Rounds vertex positions to whole pixels, which keeps text crisp in windows at
+    /// fractional positions or scales at the cost of jittery movement in animations.
+    pub fn set_pixel_snapping(&mut self, snap: bool) {
+        self.pixel_snapping = snap;
+    }
+
+    pub fn pixel_snapping(&self) -> bool {
+        self.pixel_snapping
+    }
+
+    /// Creates and registers a magenta and black checkerboard that makes broken texture
+    /// ids stand out when used with [`MissingTexturePolicy::Placeholder`].
+    pub fn create_placeholder_texture<T>(
+        &mut self,
+        texture_creator: &'a TextureCreator<T>,
+    ) -> Result<imgui::TextureId, RendererError> {
+        const SIZE: u32 = 16;
+        const CELL: u32 = 4;
+
+        let pixels: Vec<u8> = (0..SIZE * SIZE)
+            .flat_map(|i| {
+                if (i % SIZE / CELL + i / SIZE / CELL) & 1 == 0 {
+                    [255, 0, 255, 255]
+                } else {
+                    [0, 0, 0, 255]
+                }
+            })
+            .collect();
+        let texture = create_static_texture(
+            texture_creator,
+            PixelFormatEnum::RGBA32,
+            SIZE,
+            SIZE,
+            &pixels,
+            TextureOptions {
+                scale_mode: ScaleMode::Nearest,
+                ..TextureOptions::default()
+            },
+        )?;
+        Ok(self.register_texture_labeled(texture, "missing texture placeholder"))
+    }
+
+    /// Queries the driver and capabilities of the SDL renderer behind `canvas`, e.g. to
+    /// warn when SDL fell back to the software renderer.
+    pub fn info<T: RenderTarget>(canvas: &Canvas<T>) -> RendererInfo {
+        let info = canvas.info();
+        let has_flag = |flag: SDL_RendererFlags| info.flags & flag as u32 != 0;
+
+        RendererInfo {
+            driver_name: info.name,
+            software: has_flag(SDL_RendererFlags::SDL_RENDERER_SOFTWARE),
+            accelerated: has_flag(SDL_RendererFlags::SDL_RENDERER_ACCELERATED),
+            present_vsync: has_flag(SDL_RendererFlags::SDL_RENDERER_PRESENTVSYNC),
+            target_texture: has_flag(SDL_RendererFlags::SDL_RENDERER_TARGETTEXTURE),
+            max_texture_size: (info.max_texture_width, info.max_texture_height),
+            texture_formats: info.texture_formats,
+        }
+    }
+
+    /// Version of the SDL library loaded at runtime, which may differ from the one the
+    /// application was built against.
+    pub fn sdl_version() -> sdl2::version::Version {
+        sdl2::version::version()
+    }
+
+    /// Which optional SDL functions are available; all of them unless the `legacy-sdl`
+    /// feature is enabled.
+    pub fn sdl_features() -> SdlFeatures {
+        let functions = dynamic::optional_functions();
+        SdlFeatures {
+            version: sdl2::version::version(),
+            texture_scale_modes: functions.set_texture_scale_mode.is_some(),
+            nv_textures: functions.update_nv_texture.is_some(),
+            vsync_toggle: functions.render_set_vsync.is_some(),
+        }
+    }
+
+    /// Turns vsync of the canvas on or off without recreating it. This fails with
+    /// [`RendererError::UnsupportedSdlVersion`] before SDL 2.0.18, and with
+    /// [`RendererError::SdlCall`] on drivers that can't switch; either way the canvas
+    /// keeps presenting as it was created, so the application can fall back to
+    /// recreating the canvas (and renderer) with `present_vsync`.
+    pub fn set_vsync<T: RenderTarget>(
+        canvas: &mut Canvas<T>,
+        vsync: bool,
+    ) -> Result<(), RendererError> {
+        let render_set_vsync = dynamic::optional_functions()
+            .render_set_vsync
+            .ok_or_else(|| unsupported_sdl_version(18))?;
+        if unsafe { render_set_vsync(canvas.raw(), vsync as c_int) } != 0 {
+            return Err(sdl_call_error("SDL_RenderSetVSync"));
+        }
+        Ok(())
+    }
+
+    /// Render driver of the canvas the renderer was created with, e.g. `"opengl"`.
+    pub fn driver_name(&self) -> &'static str {
+        self.driver_name
+    }
+
+    /// Number of textures registered through the renderer, including the font atlas.
+    pub fn texture_count(&self) -> usize {
+        self.texture_ids.len()
+    }
+
+    /// Name the renderer reports to imgui.
+    pub fn renderer_name(&self) -> &'static str {
+        RENDERER_NAME
+    }
+
+    /// Backend flags the renderer sets on the imgui context.
+    pub fn backend_flags(&self) -> imgui::BackendFlags {
+        BACKEND_FLAGS
+    }
+
+    /// Id of the font atlas texture, or of its first band when the atlas was split.
+    pub fn font_texture_id(&self) -> imgui::TextureId {
+        self.font_texture_id
+    }
+
+    /// Rebuilds the font atlas and every texture created through
+    /// [`Renderer::create_retained_texture`], keeping their ids. Call this after
+    /// `SDL_RENDER_DEVICE_RESET` or `SDL_RENDER_TARGETS_RESET`; other textures have
+    /// to be restored by the application. Only the atlas of `imgui_context` is rebuilt;
+    /// call [`Renderer::reload_font_texture`] for each other context added with
+    /// [`Renderer::add_context`].
+    pub fn recreate_textures<T>(
+        &mut self,
+        imgui_context: &mut imgui::Context,
+        texture_creator: &'a TextureCreator<T>,
+    ) -> Result<(), RendererError> {
+        self.reload_font_texture(imgui_context, texture_creator)?;
+
+        // Scale and blend mode changes are kept in the sources; the premultiplied blend
+        // mode has no `BlendMode` value and is reapplied separately.
+        for (&texture_id, source) in &self.retained_sources {
+            let texture = create_source_texture(texture_creator, source)?;
+            if self.premultiplied_textures.contains(&texture_id) {
+                set_premultiplied_blend_mode(&texture)?;
+            }
+            self.texture_map.replace(texture_id, texture);
+        }
+
+        Ok(())
+    }
+
+    /// Destroys the GPU copies of the font atlas and of every retained texture while
+    /// keeping their ids, e.g. on `SDL_APP_WILLENTERBACKGROUND` on Android, where the
+    /// surface and its textures are lost. Call [`Renderer::recreate_textures`] (and
+    /// [`Renderer::reload_font_texture`] for other contexts) once the app is back in the
+    /// foreground; until then those ids draw as missing textures. Other textures are
+    /// left to the application.
+    pub fn release_textures(&mut self) {
+        let texture_ids: Vec<_> = self
+            .font_band_sets()
+            .flatten()
+            .map(|band| band.texture_id)
+            .chain(self.retained_sources.keys().copied())
+            .collect();
+
+        for texture_id in texture_ids {
+            self.texture_map.remove(texture_id);
+        }
+    }
+
+    /// Frees as much GPU memory as possible while the UI is hidden, e.g. in a tray or
+    /// overlay app: releases the font atlas and retained textures like
+    /// [`Renderer::release_textures`] and drops scratch textures. Other textures keep
+    /// their memory, so create long-lived ones with [`Renderer::create_retained_texture`]
+    /// to have them released too.
+    pub fn suspend(&mut self) {
+        self.release_textures();
+        for scratch in std::mem::take(&mut self.scratch_textures) {
+            self.unregister_texture(scratch.texture_id);
+        }
+        self.suspended = true;
+    }
+
+    /// Recreates what [`Renderer::suspend`] released, keeping all texture ids. As with
+    /// [`Renderer::recreate_textures`], atlases of other contexts are reloaded with
+    /// [`Renderer::reload_font_texture`].
+    pub fn resume<T>(
+        &mut self,
+        imgui_context: &mut imgui::Context,
+        texture_creator: &'a TextureCreator<T>,
+    ) -> Result<(), RendererError> {
+        self.recreate_textures(imgui_context, texture_creator)?;
+        self.suspended = false;
+        Ok(())
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Frees memory the renderer only keeps for speed, e.g. on `SDL_APP_LOWMEMORY`: scratch
+    /// textures not acquired during the current frame and the vertex buffers reused
+    /// between draw commands. A [`TextureCache`] is trimmed with [`TextureCache::clear`]
+    /// or by lowering its budget through [`TextureCache::set_budget_bytes`].
+    pub fn trim_memory(&mut self) {
+        let frame = self.frame;
+        let (idle, in_use) = std::mem::take(&mut self.scratch_textures)
+            .into_iter()
+            .partition(|scratch| scratch.acquired_frame < frame);
+        self.scratch_textures = in_use;
+        for scratch in idle {
+            self.unregister_texture(scratch.texture_id);
+        }
+
+        *self.positions.get_mut() = Vec::new();
+        *self.colors.get_mut() = Vec::new();
+        *self.font_batches.get_mut() = Vec::new();
+    }
+
+    /// Drops every registered texture except the font atlas, whose id stays valid. A
+    /// [`TextureCache`] reloads the textures it held on their next use.
+    pub fn clear_textures(&mut self) {
+        let texture_ids: Vec<_> = self
+            .texture_ids
+            .iter()
+            .filter(|&texture_id| !self.is_font_texture(texture_id))
+            .collect();
+
+        for texture_id in texture_ids {
+            self.unregister_texture(texture_id);
+        }
+        self.scratch_textures.clear();
+    }
+
+    /// Marks a texture as stored bottom-up (OpenGL convention), so
+    /// [`Renderer::texture_uvs`] returns vertically flipped UVs for it.
+    pub fn set_texture_flip_v(
+        &mut self,
+        texture_id: imgui::TextureId,
+        flip_v: bool,
+    ) -> Result<(), RendererError> {
+        self.texture_mut(texture_id)?;
+        if flip_v {
+            self.flipped_textures.insert(texture_id);
+        } else {
+            self.flipped_textures.remove(&texture_id);
+        }
+        Ok(())
+    }
+
+    pub fn texture_flip_v(&self, texture_id: imgui::TextureId) -> bool {
+        self.flipped_textures.contains(&texture_id)
+    }
+
+    /// Returns the `uv0`/`uv1` pair to draw the whole texture upright, taking its
+    /// flip setting into account.
+    pub fn texture_uvs(&self, texture_id: imgui::TextureId) -> ([f32; 2], [f32; 2]) {
+        if self.texture_flip_v(texture_id) {
+            ([0.0, 1.0], [1.0, 0.0])
+        } else {
+            ([0.0, 0.0], [1.0, 1.0])
+        }
+    }
+
+    pub fn texture_info(&self, texture_id: imgui::TextureId) -> Option<TextureInfo> {
+        let query = self.texture_map.get(texture_id)?.query();
+        Some(TextureInfo {
+            width: query.width,
+            height: query.height,
+            format: query.format,
+            access: query.access,
+        })
+    }
+
+    /// Copies a registered texture back to the CPU as tightly packed RGBA32 pixels by
+    /// blitting it into a temporary render target. The texture's color and alpha
+    /// modulation are applied to the result.
+    pub fn read_texture<T: RenderTarget>(
+        &mut self,
+        canvas: &mut Canvas<T>,
+        texture_id: imgui::TextureId,
+    ) -> Result<Vec<u8>, RendererError> {
+        if !canvas.render_target_supported() {
+            return Err(RendererError::RenderTargetsUnsupported);
+        }
+
+        let texture_creator = canvas.texture_creator();
+        let source = self.texture_mut(texture_id)?;
+        let query = source.query();
+        let mut target = texture_creator
+            .create_texture_target(PixelFormatEnum::RGBA32, query.width, query.height)
+            .map_err(|error| RendererError::TextureCreation(error.to_string()))?;
+
+        // Read as a raw value: `BlendMode` can't hold the custom mode of premultiplied
+        // textures.
+        let mut blend_mode = 0;
+        if unsafe { get_texture_custom_blend_mode(source.raw(), &mut blend_mode) } != 0 {
+            return Err(sdl_call_error("SDL_GetTextureBlendMode"));
+        }
+        let draw_color = canvas.draw_color();
+        source.set_blend_mode(BlendMode::None);
+
+        let mut pixels = Err(String::new());
+        let result = canvas.with_texture_canvas(&mut target, |target_canvas| {
+            target_canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+            target_canvas.clear();
+            pixels = target_canvas
+                .copy(source, None, None)
+                .and_then(|_| target_canvas.read_pixels(None, PixelFormatEnum::RGBA32));
+        });
+
+        unsafe { set_texture_custom_blend_mode(source.raw(), blend_mode) };
+        canvas.set_draw_color(draw_color);
+
+        result.map_err(|error| RendererError::Sdl(error.to_string()))?;
+        Ok(pixels?)
+    }
+
+    pub fn set_texture_label(
+        &mut self,
+        texture_id: imgui::TextureId,
+        label: impl Into<String>,
+    ) -> Result<(), RendererError> {
+        self.texture_mut(texture_id)?;
+        self.labels.insert(texture_id, label.into());
+        Ok(())
+    }
+
+    pub fn texture_label(&self, texture_id: imgui::TextureId) -> Option<&str> {
+        self.labels.get(&texture_id).map(String::as_str)
+    }
+
+    /// Lists every registered texture with its id, label, size and format, one per line.
+    pub fn dump_textures(&self) -> String {
+        let mut dump = String::new();
+
+        for texture_id in self.texture_ids.iter() {
+            let texture = match self.texture_map.get(texture_id) {
+                Some(texture) => texture,
+                None => continue,
+            };
+            let query = texture.query();
+
+            let _ = writeln!(
+                dump,
+                "{} {:?} {}x{} {:?}",
+                texture_id.id(),
+                self.texture_label(texture_id).unwrap_or("<unlabeled>"),
+                query.width,
+                query.height,
+                query.format
+            );
+        }
+        dump
+    }
+
+    /// Whether `texture_id` was issued by this renderer and has since been unregistered.
+    /// Ids are never handed out twice, so a stale id can't resolve to a newer texture.
+    pub fn is_texture_stale(&self, texture_id: imgui::TextureId) -> bool {
+        self.texture_ids.is_stale(texture_id)
+    }
+
+    fn missing_texture_error(&self, texture_id: imgui::TextureId) -> RendererError {
+        if self.is_texture_stale(texture_id) {
+            RendererError::StaleTexture(texture_id)
+        } else {
+            RendererError::TextureNotFound(texture_id)
+        }
+    }
+
+    pub fn textures(&self) -> &imgui::Textures<Texture<'a>> {
+        &self.texture_map
+    }
+
+    fn texture_mut(
+        &mut self,
+        texture_id: imgui::TextureId,
+    ) -> Result<&mut Texture<'a>, RendererError> {
+        let stale = self.texture_ids.is_stale(texture_id);
+        self.texture_map.get_mut(texture_id).ok_or(if stale {
+            RendererError::StaleTexture(texture_id)
+        } else {
+            RendererError::TextureNotFound(texture_id)
+        })
+    }
+
+    /// Drops every texture and undoes the renderer name, backend flags and font texture id
+    /// set on `imgui_context`, so a new renderer can be created for it afterwards (e.g.
+    /// after recreating the window).
+    pub fn destroy(self, imgui_context: &mut imgui::Context) {
+        drop(self);
+        reset_imgui_context(imgui_context);
+    }
+
+    /// Draws a frame. `draw_data` is borrowed, so the result of `imgui::Context::render`
+    /// can be passed straight through.
+    ///
+    /// The UI is mapped to output pixels with the draw data's `framebuffer_scale` alone;
+    /// a scale or logical size set on the canvas for the application's own drawing
+    /// doesn't affect it, and both are restored afterwards. With the logical size off,
+    /// `SDL_RenderSetIntegerScale` and its letterboxing don't apply either: the UI stays
+    /// on the window's pixel grid, where the platform backend reports mouse positions.
+    /// Use [`Renderer::render_integer_scaled`] to draw it on the integer-scaled grid.
+    ///
+    /// Nothing is drawn while the window is minimized or the renderer is suspended; the
+    /// returned [`RenderOutcome`] says so.
+    pub fn render<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        draw_data: &imgui::DrawData,
+    ) -> Result<RenderOutcome, RendererError> {
+        self.render_with_store(canvas, draw_data, self)
+    }
+
+    /// Clears the canvas with `clear` (if given), draws a frame and presents it, for
+    /// applications that draw nothing but the UI. Skipped frames aren't presented.
+    pub fn render_and_present<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        draw_data: &imgui::DrawData,
+        clear: Option<Color>,
+    ) -> Result<RenderOutcome, RendererError> {
+        if let Some(color) = clear {
+            let draw_color = canvas.draw_color();
+            canvas.set_draw_color(color);
+            canvas.clear();
+            canvas.set_draw_color(draw_color);
+        }
+        let outcome = self.render(canvas, draw_data)?;
+        if outcome.is_rendered() {
+            canvas.present();
+        }
+        Ok(outcome)
+    }
+
+    /// Draws a frame with textures from a map supplied by the caller, e.g. one shared by
+    /// several renderers. See [`Renderer::render_with_store`].
+    pub fn render_with_textures<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        draw_data: &imgui::DrawData,
+        textures: &imgui::Textures<Texture>,
+    ) -> Result<RenderOutcome, RendererError> {
+        self.render_with_store(canvas, draw_data, textures)
+    }
+
+    /// Draws a frame resolving texture ids through `textures` instead of the renderer's
+    /// own textures. Ids of the font atlas ([`Renderer::font_texture_id`]) still resolve
+    /// to the renderer's atlas.
+    pub fn render_with_store<T, S>(
+        &self,
+        canvas: &mut Canvas<T>,
+        draw_data: &imgui::DrawData,
+        textures: &S,
+    ) -> Result<RenderOutcome, RendererError>
+    where
+        T: RenderTarget,
+        S: TextureStore + ?Sized,
+    {
+        let frame = self.frame_geometry(
+            draw_data.display_pos,
+            draw_data.display_size,
+            draw_data.framebuffer_scale,
+            None,
+        );
+        self.render_frame(canvas, draw_data, textures, frame)
+    }
+
+    /// Draws a frame offset into `viewport`, in output pixels, and clipped to it, e.g.
+    /// to show the UI in one pane of a larger layout or over letterboxed content (see
+    /// [`letterbox_rect`]). Mouse positions reported to imgui have to be made relative
+    /// to the viewport.
+    pub fn render_in_viewport<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        draw_data: &imgui::DrawData,
+        viewport: Rect,
+    ) -> Result<RenderOutcome, RendererError> {
+        let frame = self.frame_geometry(
+            draw_data.display_pos,
+            draw_data.display_size,
+            draw_data.framebuffer_scale,
+            Some(viewport),
+        );
+        self.render_frame(canvas, draw_data, self, frame)
+    }
+
+    /// Draws a frame scaled to fill as much of `canvas` as it can without distortion,
+    /// anchored at the top-left corner. Meant for showing the same draw data in a
+    /// second window of another size, e.g. a presenter view: create a second renderer
+    /// for that window's canvas with the same imgui context. Both renderers give the
+    /// font atlas the same id; other textures have to be registered with both in the
+    /// same order, or drawn from a shared [`TextureStore`].
+    pub fn render_to_fit<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        draw_data: &imgui::DrawData,
+    ) -> Result<RenderOutcome, RendererError> {
+        let (width, height) = canvas.output_size()?;
+        let [display_width, display_height] = draw_data.display_size;
+        let frame = self
+            .frame_geometry(
+                draw_data.display_pos,
+                draw_data.display_size,
+                draw_data.framebuffer_scale,
+                Some(Rect::new(0, 0, width, height)),
+            )
+            .map(|frame| {
+                let scale = (frame.fb_width / display_width).min(frame.fb_height / display_height);
+                FrameGeometry {
+                    clip_scale: [scale * self.ui_scale; 2],
+                    ..frame
+                }
+            });
+        self.render_frame(canvas, draw_data, self, frame)
+    }
+
+    /// Draws a frame on the grid of a canvas with a logical size and
+    /// `SDL_RenderSetIntegerScale` on, for retro-style frontends that set imgui's display
+    /// size to the logical size: the UI is scaled by the same whole factor as the
+    /// application's content and offset into its letterbox, and so are the scissor rects.
+    /// SDL already reports mouse positions in logical coordinates there. Canvases without
+    /// a logical size or integer scaling are drawn as by [`Renderer::render`].
+    pub fn render_integer_scaled<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        draw_data: &imgui::DrawData,
+    ) -> Result<RenderOutcome, RendererError> {
+        let presentation = if canvas.integer_scale() {
+            integer_scale_viewport(canvas.output_size()?, canvas.logical_size())
+        } else {
+            None
+        };
+        let (viewport, scale) = match presentation {
+            Some(presentation) => presentation,
+            None => return self.render(canvas, draw_data),
+        };
+
+        let frame = self.frame_geometry(
+            draw_data.display_pos,
+            draw_data.display_size,
+            [scale as f32; 2],
+            Some(viewport),
+        );
+        self.render_frame(canvas, draw_data, self, frame)
+    }
+
+    fn render_frame<T, S>(
+        &self,
+        canvas: &mut Canvas<T>,
+        draw_data: &imgui::DrawData,
+        textures: &S,
+        frame: Option<FrameGeometry>,
+    ) -> Result<RenderOutcome, RendererError>
+    where
+        T: RenderTarget,
+        S: TextureStore + ?Sized,
+    {
+        let frame = match self.check_frame(frame) {
+            Ok(frame) => frame,
+            Err(reason) => return Ok(RenderOutcome::Skipped(reason)),
+        };
+
+        let mut stats = RenderStats::default();
+        self.draw_ui(canvas, |canvas| {
+            for (list_index, draw_list) in draw_data.draw_lists().enumerate() {
+                self.render_list(canvas, &frame, textures, list_index, draw_list, &mut stats)?;
+            }
+            Ok(())
+        })?;
+        Ok(RenderOutcome::Rendered(stats))
+    }
+
+    fn check_frame(&self, frame: Option<FrameGeometry>) -> Result<FrameGeometry, SkipReason> {
+        if self.suspended {
+            return Err(SkipReason::Suspended);
+        }
+        frame.ok_or(SkipReason::EmptyFramebuffer)
+    }
+
+    /// Draws a single draw list, so the application can draw its own content between
+    /// lists (e.g. the game between imgui's background and foreground lists). The
+    /// geometry arguments are the `display_pos`, `display_size` and `framebuffer_scale`
+    /// of the draw data the list belongs to.
+    pub fn render_draw_list<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        draw_list: &imgui::DrawList,
+        display_pos: [f32; 2],
+        display_size: [f32; 2],
+        framebuffer_scale: [f32; 2],
+    ) -> Result<RenderOutcome, RendererError> {
+        let frame = self.frame_geometry(display_pos, display_size, framebuffer_scale, None);
+        let frame = match self.check_frame(frame) {
+            Ok(frame) => frame,
+            Err(reason) => return Ok(RenderOutcome::Skipped(reason)),
+        };
+
+        let mut stats = RenderStats::default();
+        self.draw_ui(canvas, |canvas| {
+            self.render_list(canvas, &frame, self, 0, draw_list, &mut stats)
+        })?;
+        Ok(RenderOutcome::Rendered(stats))
+    }
+
+    fn render_list<T, S>(
+        &self,
+        canvas: &mut Canvas<T>,
+        frame: &FrameGeometry,
+        textures: &S,
+        list_index: usize,
+        draw_list: &imgui::DrawList,
+        stats: &mut RenderStats,
+    ) -> Result<(), RendererError>
+    where
+        T: RenderTarget,
+        S: TextureStore + ?Sized,
+    {
+        let idx_buffer: &[imgui::DrawIdx] = draw_list.idx_buffer();
+        let vtx_buffer = draw_list.vtx_buffer();
+        stats.draw_lists += 1;
+
+        for (command_index, command) in draw_list.commands().enumerate() {
+            match command {
+                imgui::DrawCmd::Elements { count, cmd_params } => {
+                    self.validate_command(
+                        list_index,
+                        command_index,
+                        vtx_buffer,
+                        idx_buffer,
+                        count,
+                        &cmd_params,
+                    )?;
+                    self.render_elements(
+                        canvas,
+                        frame,
+                        textures,
+                        vtx_buffer,
+                        idx_buffer,
+                        count,
+                        &cmd_params,
+                    )
+                    .map_err(|error| error.at_command(list_index, command_index))?;
+                    stats.draw_commands += 1;
+                    stats.indices += count;
+                }
+                imgui::DrawCmd::RawCallback { callback, raw_cmd } => unsafe {
+                    callback(draw_list.raw(), raw_cmd)
+                },
+                imgui::DrawCmd::ResetRenderState => Self::reset_render_state(canvas)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws a frame from draw data snapshotted earlier with [`OwnedDrawData::new`],
+    /// possibly on another thread.
+    pub fn render_owned<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        draw_data: &OwnedDrawData,
+    ) -> Result<RenderOutcome, RendererError> {
+        let frame = self.frame_geometry(
+            draw_data.display_pos,
+            draw_data.display_size,
+            draw_data.framebuffer_scale,
+            None,
+        );
+        let frame = match self.check_frame(frame) {
+            Ok(frame) => frame,
+            Err(reason) => return Ok(RenderOutcome::Skipped(reason)),
+        };
+
+        let mut stats = RenderStats::default();
+        self.draw_ui(canvas, |canvas| {
+            for (list_index, draw_list) in draw_data.draw_lists.iter().enumerate() {
+                stats.draw_lists += 1;
+                for (command_index, command) in draw_list.commands.iter().enumerate() {
+                    match command {
+                        OwnedDrawCmd::Elements { count, cmd_params } => {
+                            self.validate_command(
+                                list_index,
+                                command_index,
+                                &draw_list.vtx_buffer,
+                                &draw_list.idx_buffer,
+                                *count,
+                                cmd_params,
+                            )?;
+                            self.render_elements(
+                                canvas,
+                                &frame,
+                                self,
+                                &draw_list.vtx_buffer,
+                                &draw_list.idx_buffer,
+                                *count,
+                                cmd_params,
+                            )
+                            .map_err(|error| error.at_command(list_index, command_index))?;
+                            stats.draw_commands += 1;
+                            stats.indices += count;
+                        }
+                        OwnedDrawCmd::ResetRenderState => Self::reset_render_state(canvas)?,
+                    }
+                }
+            }
+            Ok(())
+        })?;
+        Ok(RenderOutcome::Rendered(stats))
+    }
+
+    /// Runs the draw data checks when enabled, failing on issues that would draw garbage
+    /// and recording the others for [`Renderer::draw_data_issues`].
+    fn validate_command(
+        &self,
+        list_index: usize,
+        command_index: usize,
+        vtx_buffer: &[imgui::DrawVert],
+        idx_buffer: &[imgui::DrawIdx],
+        count: usize,
+        cmd_params: &imgui::DrawCmdParams,
+    ) -> Result<(), RendererError> {
+        if !self.validate_draw_data {
+            return Ok(());
+        }
+
+        let mut draw_data_issues = self.draw_data_issues.borrow_mut();
+        for kind in validate::check_elements(vtx_buffer, idx_buffer, count, cmd_params) {
+            if kind.is_fatal() {
+                return Err(RendererError::InvalidDrawData(kind.to_string())
+                    .at_command(list_index, command_index));
+            }
+            draw_data_issues.push(DrawDataIssue {
+                draw_list: list_index,
+                command: command_index,
+                kind,
+            });
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_elements<T, S>(
+        &self,
+        canvas: &mut Canvas<T>,
+        frame: &FrameGeometry,
+        textures: &S,
+        vtx_buffer: &[imgui::DrawVert],
+        idx_buffer: &[imgui::DrawIdx],
+        count: usize,
+        cmd_params: &imgui::DrawCmdParams,
+    ) -> Result<(), RendererError>
+    where
+        T: RenderTarget,
+        S: TextureStore + ?Sized,
+    {
+        let FrameGeometry {
+            fb_width,
+            fb_height,
+            clip_off,
+            clip_scale,
+            origin,
+            ..
+        } = *frame;
+
+        let clip_min = [
+            (cmd_params.clip_rect[0] - clip_off[0]) * clip_scale[0],
+            (cmd_params.clip_rect[1] - clip_off[1]) * clip_scale[1],
+        ];
+        let clip_max = [
+            (cmd_params.clip_rect[2] - clip_off[0]) * clip_scale[0],
+            (cmd_params.clip_rect[3] - clip_off[1]) * clip_scale[1],
+        ];
+        if clip_min.iter().chain(&clip_max).any(|value| value.is_nan()) {
+            return Ok(());
+        }
+
+        // Sentinel rects like `[-FLT_MAX, -FLT_MAX, FLT_MAX, FLT_MAX]` may have overflowed
+        // to infinity above; clamping both corners into the framebuffer handles them and
+        // rects lying entirely outside it alike.
+        let clip_min = [
+            clip_min[0].clamp(0.0, fb_width),
+            clip_min[1].clamp(0.0, fb_height),
+        ];
+        let clip_max = [
+            clip_max[0].clamp(0.0, fb_width),
+            clip_max[1].clamp(0.0, fb_height),
+        ];
+        if clip_max[0] <= clip_min[0] || clip_max[1] <= clip_min[1] {
+            return Ok(());
+        }
+
+        let (vertices, indices) = command_slices(vtx_buffer, idx_buffer, count, cmd_params)?;
+        let num_vertices = c_int::try_from(vertices.len()).map_err(|_| {
+            RendererError::InvalidDrawData(format!("{} vertices in one draw call", vertices.len()))
+        })?;
+
+        let texture = if self.is_font_texture(cmd_params.texture_id) {
+            self.texture_map.get(cmd_params.texture_id)
+        } else {
+            textures.texture(cmd_params.texture_id)
+        };
+        let texture = match texture {
+            Some(texture) => Some(texture),
+            None => match self.missing_texture_policy {
+                MissingTexturePolicy::Untextured => None,
+                MissingTexturePolicy::Skip => return Ok(()),
+                MissingTexturePolicy::Error if textures.is_stale(cmd_params.texture_id) => {
+                    return Err(RendererError::StaleTexture(cmd_params.texture_id))
+                }
+                MissingTexturePolicy::Error => {
+                    return Err(RendererError::TextureNotFound(cmd_params.texture_id))
+                }
+                MissingTexturePolicy::Placeholder(texture_id) => textures
+                    .texture(texture_id)
+                    .or_else(|| self.texture_map.get(texture_id)),
+            },
+        };
+
+        let clip_min = clip_min.map(|value| self.clip_rounding.round_min(value));
+        let clip_max = clip_max.map(|value| self.clip_rounding.round_max(value));
+        if clip_max[0] <= clip_min[0] || clip_max[1] <= clip_min[1] {
+            return Ok(());
+        }
+
+        let (corner_a, corner_b) = (frame.orient(clip_min), frame.orient(clip_max));
+        let clip_min = [corner_a[0].min(corner_b[0]), corner_a[1].min(corner_b[1])];
+        let clip_max = [corner_a[0].max(corner_b[0]), corner_a[1].max(corner_b[1])];
+
+        // The clip bounds are finite and within the framebuffer (give or take the
+        // rounding) at this point, and float to int `as` casts saturate, so none of
+        // these can wrap or panic.
+        let rect = Rect::new(
+            (clip_min[0] + origin[0]) as i32,
+            (clip_min[1] + origin[1]) as i32,
+            (clip_max[0] - clip_min[0]) as u32,
+            (clip_max[1] - clip_min[1]) as u32,
+        );
+        if self.scissor_clipping {
+            canvas.set_clip_rect(rect);
+        }
+
+        unsafe {
+            let font_bands = self
+                .font_band_sets()
+                .find(|bands| bands[0].texture_id == cmd_params.texture_id);
+            if let Some(bands) = font_bands {
+                if bands.len() > 1 {
+                    return self.render_font(canvas.raw(), frame, bands, vertices, indices);
+                }
+            }
+
+            let vertices_ptr = vertices.as_ptr() as usize;
+
+            let premultiplied = self.premultiplied_textures.contains(&cmd_params.texture_id);
+            let mut colors = self.colors.borrow_mut();
+            let converts_colors =
+                premultiplied || self.global_tint != Color::WHITE || cfg!(target_endian = "big");
+            let (color_ptr, color_stride) = if converts_colors {
+                colors.clear();
+                colors.extend(
+                    vertices
+                        .iter()
+                        .map(|vertex| self.vertex_color(vertex, premultiplied)),
+                );
+                (colors.as_ptr() as usize, size_of::<SDL_Color>())
+            } else {
+                (
+                    vertices_ptr + memoffset::offset_of!(imgui::DrawVert, col),
+                    size_of::<imgui::DrawVert>(),
+                )
+            };
+
+            let mut positions = self.positions.borrow_mut();
+            let (position_ptr, position_stride) = if frame.transforms_vertices() {
+                positions.clear();
+                positions.extend(vertices.iter().map(|vertex| frame.transform(vertex.pos)));
+                (positions.as_ptr() as usize, size_of::<[f32; 2]>())
+            } else {
+                (
+                    vertices_ptr + memoffset::offset_of!(imgui::DrawVert, pos),
+                    size_of::<imgui::DrawVert>(),
+                )
+            };
+            let uv_field_offset = vertices_ptr + memoffset::offset_of!(imgui::DrawVert, uv);
+
+            for indices in indices.chunks(MAX_GEOMETRY_INDICES) {
+                let result = SDL_RenderGeometryRaw(
+                    canvas.raw(),
+                    match texture {
+                        Some(texture) => texture.raw(),
+                        None => null_mut(),
+                    },
+                    position_ptr as *const f32,
+                    position_stride as _,
+                    color_ptr as *const SDL_Color,
+                    color_stride as _,
+                    uv_field_offset as *const f32,
+                    size_of::<imgui::DrawVert>() as _,
+                    num_vertices,
+                    indices.as_ptr().cast(),
+                    indices.len() as c_int,
+                    size_of::<imgui::DrawIdx>() as _,
+                );
+                if result != 0 {
+                    return Err(sdl_call_error("SDL_RenderGeometryRaw"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws a registered texture centered over the whole canvas, e.g. an image or video
+    /// frame shown behind the UI. Call it before [`Renderer::render`]; the canvas state
+    /// is left as it was.
+    pub fn draw_background_texture<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        texture_id: imgui::TextureId,
+        fit: BackgroundFit,
+    ) -> Result<(), RendererError> {
+        let texture = self
+            .texture_map
+            .get(texture_id)
+            .ok_or_else(|| self.missing_texture_error(texture_id))?;
+        let query = texture.query();
+        let flip_v = self.texture_flip_v(texture_id);
+
+        with_backup_state(canvas, |canvas| {
+            Self::setup_render_state(canvas);
+            let area = canvas.viewport();
+            let (width, height) = (area.width() as f32, area.height() as f32);
+            if area.width() == 0 || area.height() == 0 || query.width == 0 || query.height == 0 {
+                return Ok(());
+            }
+
+            let scale_x = width / query.width as f32;
+            let scale_y = height / query.height as f32;
+            let (scale_x, scale_y) = match fit {
+                BackgroundFit::Stretch => (scale_x, scale_y),
+                BackgroundFit::Fit => (scale_x.min(scale_y), scale_x.min(scale_y)),
+                BackgroundFit::Cover => (scale_x.max(scale_y), scale_x.max(scale_y)),
+            };
+            let dst_width = query.width as f32 * scale_x;
+            let dst_height = query.height as f32 * scale_y;
+            let dst = Rect::new(
+                ((width - dst_width) / 2.0).round() as i32,
+                ((height - dst_height) / 2.0).round() as i32,
+                dst_width.round() as u32,
+                dst_height.round() as u32,
+            );
+
+            canvas.copy_ex(texture, None, dst, 0.0, None, false, flip_v)?;
+            Ok(())
+        })
+    }
+
+    /// Renders the UI into a texture created with [`Renderer::create_target_texture`]
+    /// instead of the window, restoring the previous render target afterwards. The UI
+    /// is drawn over the texture's current contents.
+    pub fn render_to_texture<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+        target_texture_id: imgui::TextureId,
+        draw_data: &imgui::DrawData,
+    ) -> Result<RenderOutcome, RendererError> {
+        if !canvas.render_target_supported() {
+            return Err(RendererError::RenderTargetsUnsupported);
+        }
+
+        let target = self
+            .texture_map
+            .get(target_texture_id)
+            .ok_or_else(|| self.missing_texture_error(target_texture_id))?;
+
+        let raw_canvas = canvas.raw();
+        let previous_target = unsafe { SDL_GetRenderTarget(raw_canvas) };
+        if unsafe { SDL_SetRenderTarget(raw_canvas, target.raw()) } != 0 {
+            return Err(sdl_call_error("SDL_SetRenderTarget"));
+        }
+
+        let result = self.render(canvas, draw_data);
+
+        if unsafe { SDL_SetRenderTarget(raw_canvas, previous_target) } != 0 && result.is_ok() {
+            return Err(sdl_call_error("SDL_SetRenderTarget"));
+        }
+        result
+    }
+
+    /// Renders `draw_data` of `imgui_context` into a new `width`x`height` RGBA surface
+    /// with SDL's software renderer, without needing a window, e.g. for screenshots in
+    /// documentation. Only the font atlas is available to it; other textures follow the
+    /// default [`MissingTexturePolicy`].
+    pub fn render_to_surface(
+        imgui_context: &mut imgui::Context,
+        draw_data: &OwnedDrawData,
+        width: u32,
+        height: u32,
+    ) -> Result<Surface<'static>, RendererError> {
+        let surface = Surface::new(width, height, PixelFormatEnum::RGBA32)
+            .map_err(RendererError::TextureCreation)?;
+        let mut canvas = surface.into_canvas()?;
+        let texture_creator = canvas.texture_creator();
+
+        let font_texture_id = imgui_context.fonts().tex_id;
+        let renderer = Renderer::new(&mut canvas, imgui_context, &texture_creator);
+        imgui_context.fonts().tex_id = font_texture_id;
+        let renderer = renderer?;
+
+        // The draw data refers to the font atlas by the id of the context's own renderer.
+        let mut draw_data = draw_data.clone();
+        for draw_list in &mut draw_data.draw_lists {
+            for command in &mut draw_list.commands {
+                if let OwnedDrawCmd::Elements { cmd_params, .. } = command {
+                    if cmd_params.texture_id == font_texture_id {
+                        cmd_params.texture_id = renderer.font_texture_id;
+                    }
+                }
+            }
+        }
+        let _ = renderer.render_owned(&mut canvas, &draw_data)?;
+        drop(renderer);
+
+        Ok(canvas.into_surface())
+    }
+
+    /// Draws triangles sampling a font atlas split into bands, routing each triangle to the
+    /// band containing all of its texture coordinates.
+    unsafe fn render_font(
+        &self,
+        renderer: *mut SDL_Renderer,
+        frame: &FrameGeometry,
+        bands: &[FontBand],
+        vertices: &[imgui::DrawVert],
+        indices: &[imgui::DrawIdx],
+    ) -> Result<(), RendererError> {
+        let mut batches = self.font_batches.borrow_mut();
+        batches.resize_with(bands.len(), Vec::new);
+        for batch in batches.iter_mut() {
+            batch.clear();
+        }
+
+        for triangle in indices.chunks_exact(3) {
+            let corners = match (
+                vertices.get(triangle[0] as usize),
+                vertices.get(triangle[1] as usize),
+                vertices.get(triangle[2] as usize),
+            ) {
+                (Some(a), Some(b), Some(c)) => [a, b, c],
+                _ => continue,
+            };
+
+            let min_v = corners
+                .iter()
+                .map(|vertex| vertex.uv[1])
+                .fold(f32::MAX, f32::min);
+            let max_v = corners
+                .iter()
+                .map(|vertex| vertex.uv[1])
+                .fold(f32::MIN, f32::max);
+            let band_index = bands
+                .iter()
+                .position(|band| band.top <= min_v && max_v <= band.bottom)
+                .or_else(|| bands.iter().rposition(|band| band.top <= min_v))
+                .unwrap_or(0);
+            let band = &bands[band_index];
+            let premultiplied = self.premultiplied_textures.contains(&band.texture_id);
+
+            for vertex in corners {
+                batches[band_index].push(SDL_Vertex {
+                    position: {
+                        let [x, y] = frame.transform(vertex.pos);
+                        SDL_FPoint { x, y }
+                    },
+                    color: self.vertex_color(vertex, premultiplied),
+                    tex_coord: SDL_FPoint {
+                        x: vertex.uv[0],
+                        y: (vertex.uv[1] - band.top) / (band.bottom - band.top),
+                    },
+                });
+            }
+        }
+
+        for (band, batch) in bands.iter().zip(batches.iter()) {
+            let texture = match self.texture_map.get(band.texture_id) {
+                Some(texture) => texture.raw(),
+                None => null_mut(),
+            };
+
+            for batch in batch.chunks(MAX_GEOMETRY_INDICES) {
+                let result = SDL_RenderGeometry(
+                    renderer,
+                    texture,
+                    batch.as_ptr(),
+                    batch.len() as _,
+                    null(),
+                    0,
+                );
+                if result != 0 {
+                    return Err(sdl_call_error("SDL_RenderGeometry"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn setup_render_state<T: RenderTarget>(canvas: &mut Canvas<T>) {
+        canvas.set_clip_rect(None);
+        canvas.set_viewport(None);
+        canvas.set_blend_mode(BlendMode::Blend);
+    }
+
+    fn frame_geometry(
+        &self,
+        display_pos: [f32; 2],
+        display_size: [f32; 2],
+        framebuffer_scale: [f32; 2],
+        viewport: Option<Rect>,
+    ) -> Option<FrameGeometry> {
+        let mut frame = FrameGeometry::new(display_pos, display_size, framebuffer_scale)?;
+        frame.clip_scale = frame.clip_scale.map(|scale| scale * self.ui_scale);
+        frame.rotation = self.output_rotation;
+        frame.mirror = self.output_mirror;
+        frame.snap_to_pixels = self.pixel_snapping;
+
+        if let Some(viewport) = viewport {
+            let (width, height) = (viewport.width() as f32, viewport.height() as f32);
+            (frame.fb_width, frame.fb_height) = match self.output_rotation {
+                OutputRotation::None | OutputRotation::Rotate180 => (width, height),
+                OutputRotation::Rotate90 | OutputRotation::Rotate270 => (height, width),
+            };
+            frame.origin = [viewport.x() as f32, viewport.y() as f32];
+        }
+        Some(frame)
+    }
+
+    /// Converts a vertex color for SDL, applying the global tint.
+    fn vertex_color(&self, vertex: &imgui::DrawVert, premultiplied: bool) -> SDL_Color {
+        let [r, g, b, a] = vertex_rgba(vertex);
+        let tint = self.global_tint;
+        let (r, g, b, a) = (
+            premultiply(r, tint.r),
+            premultiply(g, tint.g),
+            premultiply(b, tint.b),
+            premultiply(a, tint.a),
+        );
+        if premultiplied {
+            SDL_Color {
+                r: premultiply(r, a),
+                g: premultiply(g, a),
+                b: premultiply(b, a),
+                a,
+            }
+        } else {
+            SDL_Color { r, g, b, a }
+        }
+    }
+
+    /// Runs `draw` with the UI's render state set up, flushing SDL's command queue around
+    /// it if requested and restoring the canvas state afterwards.
+    fn draw_ui<T, F>(&self, canvas: &mut Canvas<T>, draw: F) -> Result<(), RendererError>
+    where
+        T: RenderTarget,
+        F: FnOnce(&mut Canvas<T>) -> Result<(), RendererError>,
+    {
+        if self.flush_before && unsafe { SDL_RenderFlush(canvas.raw()) } != 0 {
+            return Err(sdl_call_error("SDL_RenderFlush"));
+        }
+        with_backup_state(canvas, |canvas| {
+            Self::reset_render_state(canvas)?;
+            draw(canvas)
+        })?;
+        if self.flush_after && unsafe { SDL_RenderFlush(canvas.raw()) } != 0 {
+            return Err(sdl_call_error("SDL_RenderFlush"));
+        }
+        Ok(())
+    }
+
+    /// [`Renderer::setup_render_state`] plus the unit scale the UI is drawn with. A
+    /// logical size is switched off, as it would scale and letterbox the UI.
+    fn reset_render_state<T: RenderTarget>(canvas: &mut Canvas<T>) -> Result<(), RendererError> {
+        let reset = RenderStateReset::for_canvas(canvas.logical_size(), canvas.scale());
+        if reset.clear_logical_size {
+            canvas
+                .set_logical_size(0, 0)
+                .map_err(|error| RendererError::Sdl(error.to_string()))?;
+        }
+        if reset.reset_scale {
+            canvas.set_scale(1.0, 1.0).map_err(RendererError::Sdl)?;
+        }
+        Self::setup_render_state(canvas);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for Renderer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Renderer")
+            .field("renderer_name", &RENDERER_NAME)
+            .field("backend_flags", &BACKEND_FLAGS)
+            .field("driver_name", &self.driver_name)
+            .field("texture_count", &self.texture_count())
+            .field("font_texture_id", &self.font_texture_id)
+            .field("font_bands", &self.font_bands.len())
+            .field("font_options", &self.font_options)
+            .field("max_texture_size", &self.max_texture_size)
+            .field("dpi_scale", &self.dpi_scale)
+            .field("missing_texture_policy", &self.missing_texture_policy)
+            .field("validate_draw_data", &self.validate_draw_data)
+            .field("clip_rounding", &self.clip_rounding)
+            .field("scissor_clipping", &self.scissor_clipping)
+            .field("pixel_snapping", &self.pixel_snapping)
+            .field("premultiplied_alpha", &self.premultiplied_alpha)
+            .field("default_blend_mode", &self.default_blend_mode)
+            .field("global_tint", &self.global_tint)
+            .field("ui_scale", &self.ui_scale)
+            .field("output_rotation", &self.output_rotation)
+            .field("output_mirror", &self.output_mirror)
+            .field("flush_before", &self.flush_before)
+            .field("flush_after", &self.flush_after)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Renderer<'_> {
+    fn drop(&mut self) {
+        for texture_id in std::mem::take(&mut self.external_textures) {
+            if let Some(texture) = self.texture_map.remove(texture_id) {
+                std::mem::forget(texture);
+            }
+        }
+    }
+}
+
+fn init_imgui_context(imgui_context: &mut imgui::Context) {
+    imgui_context.set_renderer_name(RENDERER_NAME.to_owned());
+    imgui_context.io_mut().backend_flags.insert(BACKEND_FLAGS);
+}
+
+pub(crate) fn reset_imgui_context(imgui_context: &mut imgui::Context) {
+    imgui_context.set_renderer_name(None);
+    imgui_context.io_mut().backend_flags.remove(BACKEND_FLAGS);
+    imgui_context.fonts().tex_id = imgui::TextureId::new(0);
+}
+
+/// Runs `draw` and restores the canvas render target, logical size, scale, clip rect,
+/// viewport, blend mode and draw color afterwards, even when it fails, so draw callbacks
+/// can't leak state into the host application.
+fn with_backup_state<T, F>(canvas: &mut Canvas<T>, draw: F) -> Result<(), RendererError>
+where
+    T: RenderTarget,
+    F: FnOnce(&mut Canvas<T>) -> Result<(), RendererError>,
+{
+    let mut blend_mode = 0;
+    if unsafe { get_render_draw_custom_blend_mode(canvas.raw(), &mut blend_mode) } != 0 {
+        return Err(sdl_call_error("SDL_GetRenderDrawBlendMode"));
+    }
+    let backup = BackupSDLRendererState {
+        render_target: unsafe { SDL_GetRenderTarget(canvas.raw()) },
+        logical_size: canvas.logical_size(),
+        scale: canvas.scale(),
+        clip_rect: canvas.clip_rect(),
+        viewport: canvas.viewport(),
+        blend_mode,
+        draw_color: canvas.draw_color(),
+    };
+
+    // Untextured geometry is blended with the canvas blend mode.
+    canvas.set_blend_mode(BlendMode::Blend);
+    let result = draw(canvas);
+
+    // Targets and logical size are only put back when changed, since setting them also
+    // resets the scale and viewport, which are restored afterwards.
+    let mut restored = Ok(());
+    if unsafe { SDL_GetRenderTarget(canvas.raw()) } != backup.render_target
+        && unsafe { SDL_SetRenderTarget(canvas.raw(), backup.render_target) } != 0
+    {
+        restored = Err(sdl_call_error("SDL_SetRenderTarget"));
+    }
+    if canvas.logical_size() != backup.logical_size {
+        let (width, height) = backup.logical_size;
+        if let Err(error) = canvas.set_logical_size(width, height) {
+            restored = Err(RendererError::Sdl(error.to_string()));
+        }
+    }
+    if let Err(error) = canvas.set_scale(backup.scale.0, backup.scale.1) {
+        restored = Err(RendererError::Sdl(error));
+    }
+    canvas.set_clip_rect(backup.clip_rect);
+    canvas.set_viewport(backup.viewport);
+    if unsafe { set_render_draw_custom_blend_mode(canvas.raw(), backup.blend_mode) } != 0 {
+        restored = Err(sdl_call_error("SDL_SetRenderDrawBlendMode"));
+    }
+    canvas.set_draw_color(backup.draw_color);
+
+    result.and(restored)
+}
+
+/// Uploads the font atlas, split into overlapping horizontal bands when it is taller
+/// than the renderer's maximum texture height. Each texture comes with the vertical
+/// range of the atlas it covers, in UV space.
+fn create_font_textures<'a, T>(
+    imgui_context: &mut imgui::Context,
+    texture_creator: &'a TextureCreator<T>,
+    options: FontAtlasOptions,
+) -> Result<Vec<(Texture<'a>, f32, f32)>, RendererError> {
+    let mut fonts = imgui_context.fonts();
+
+    let (format, width, height, pixels) = match options.format {
+        FontAtlasFormat::Rgba32 => {
+            let imgui::FontAtlasTexture {
+                data,
+                height,
+                width,
+            } = fonts.build_rgba32_texture();
+            let pixels = if options.premultiplied_alpha {
+                Cow::Owned(
+                    data.chunks_exact(4)
+                        .flat_map(|pixel| {
+                            let alpha = pixel[3];
+                            [
+                                premultiply(pixel[0], alpha),
+                                premultiply(pixel[1], alpha),
+                                premultiply(pixel[2], alpha),
+                                alpha,
+                            ]
+                        })
+                        .collect(),
+                )
+            } else {
+                Cow::Borrowed(data)
+            };
+            (PixelFormatEnum::RGBA32, width, height, pixels)
+        }
+        FontAtlasFormat::Alpha8 => {
+            let imgui::FontAtlasTexture {
+                data,
+                height,
+                width,
+            } = fonts.build_alpha8_texture();
+            let mapping = if options.premultiplied_alpha {
+                GrayscaleMapping::ReplicateWithAlpha
+            } else {
+                GrayscaleMapping::WhiteWithAlpha
+            };
+            let pixels = data
+                .iter()
+                .flat_map(|&alpha| mapping.expand(alpha))
+                .collect();
+            (PixelFormatEnum::RGBA32, width, height, Cow::Owned(pixels))
+        }
+        FontAtlasFormat::Alpha8Argb4444 => {
+            let imgui::FontAtlasTexture {
+                data,
+                height,
+                width,
+            } = fonts.build_alpha8_texture();
+            let pixels = data
+                .iter()
+                .flat_map(|&alpha| {
+                    let alpha = (alpha as u16) >> 4;
+                    let color = if options.premultiplied_alpha {
+                        alpha << 8 | alpha << 4 | alpha
+                    } else {
+                        0x0fff
+                    };
+                    (alpha << 12 | color).to_ne_bytes()
+                })
+                .collect();
+            (PixelFormatEnum::ARGB4444, width, height, Cow::Owned(pixels))
+        }
+    };
+
+    // Only tall atlases can be split, so the width has to fit as is.
+    let (max_width, max_height) = max_texture_size(texture_creator.raw());
+    if max_width != 0 && width > max_width {
+        return Err(RendererError::TextureTooLarge {
+            width,
+            height,
+            max_width,
+            max_height,
+        });
+    }
+
+    let texture_options = TextureOptions {
+        scale_mode: options.scale_mode,
+        ..TextureOptions::default()
+    };
+
+    if max_height == 0 || height <= max_height {
+        let texture = create_static_texture(
+            texture_creator,
+            format,
+            width,
+            height,
+            &pixels,
+            texture_options,
+        )?;
+        if options.premultiplied_alpha {
+            set_premultiplied_blend_mode(&texture)?;
+        }
+        return Ok(vec![(texture, 0.0, 1.0)]);
+    }
+
+    // Bands overlap by a quarter of their height so that every glyph fits entirely
+    // inside at least one of them.
+    let pitch = format.byte_size_of_pixels(width as usize);
+    let step = max_height - max_height / 4;
+    let mut bands = Vec::new();
+    let mut top = 0;
+
+    loop {
+        let band_height = max_height.min(height - top);
+        let start = top as usize * pitch;
+        let end = (top + band_height) as usize * pitch;
+        let texture = create_static_texture(
+            texture_creator,
+            format,
+            width,
+            band_height,
+            pixels.get(start..end).unwrap_or_default(),
+            texture_options,
+        )?;
+        if options.premultiplied_alpha {
+            set_premultiplied_blend_mode(&texture)?;
+        }
+        bands.push((
+            texture,
+            top as f32 / height as f32,
+            (top + band_height) as f32 / height as f32,
+        ));
+
+        if top + band_height >= height {
+            break;
+        }
+        top += step;
+    }
+
+    Ok(bands)
+}
+
+fn check_texture_size<T>(
+    texture_creator: &TextureCreator<T>,
+    width: u32,
+    height: u32,
+) -> Result<(), RendererError> {
+    let (max_width, max_height) = max_texture_size(texture_creator.raw());
+    if (max_width != 0 && width > max_width) || (max_height != 0 && height > max_height) {
+        return Err(RendererError::TextureTooLarge {
+            width,
+            height,
+            max_width,
+            max_height,
+        });
+    }
+    Ok(())
+}
+
+/// Maximum texture size reported by the renderer, 0 meaning unknown or unlimited.
+fn max_texture_size(renderer: *mut SDL_Renderer) -> (u32, u32) {
+    let mut info = MaybeUninit::<SDL_RendererInfo>::uninit();
+    unsafe {
+        if SDL_GetRendererInfo(renderer, info.as_mut_ptr()) != 0 {
+            return (0, 0);
+        }
+        let info = info.assume_init();
+        (
+            info.max_texture_width.max(0) as u32,
+            info.max_texture_height.max(0) as u32,
+        )
+    }
+}
+
+fn create_static_texture<'a, T>(
+    texture_creator: &'a TextureCreator<T>,
+    format: PixelFormatEnum,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    options: TextureOptions,
+) -> Result<Texture<'a>, RendererError> {
+    check_texture_size(texture_creator, width, height)?;
+
+    let pitch = packed_pitch(format, width)?;
+    let required = pitch * height as usize;
+    if pixels.len() < required {
+        return Err(RendererError::InvalidPixelData(format!(
+            "expected {} bytes of {:?} pixel data for a {}x{} texture, got {}",
+            required,
+            format,
+            width,
+            height,
+            pixels.len()
+        )));
+    }
+
+    let mut texture = texture_creator
+        .create_texture_static(format, width, height)
+        .map_err(|error| RendererError::TextureCreation(error.to_string()))?;
+
+    texture
+        .update(None, pixels, pitch)
+        .map_err(|error| RendererError::TextureUpload(error.to_string()))?;
+    apply_texture_options(&mut texture, options)?;
+
+    Ok(texture)
+}
+
+fn create_source_texture<'a, T>(
+    texture_creator: &'a TextureCreator<T>,
+    source: &TextureSource,
+) -> Result<Texture<'a>, RendererError> {
+    create_static_texture(
+        texture_creator,
+        source.format,
+        source.width,
+        source.height,
+        &source.pixels,
+        source.options,
+    )
+}
+
+fn create_surface_texture<'a, T>(
+    texture_creator: &'a TextureCreator<T>,
+    surface: &SurfaceRef,
+    options: TextureOptions,
+) -> Result<Texture<'a>, RendererError> {
+    let surface = surface
+        .convert_format(PixelFormatEnum::RGBA32)
+        .map_err(RendererError::TextureCreation)?;
+
+    let mut texture = texture_creator
+        .create_texture_from_surface(&surface)
+        .map_err(|error| RendererError::TextureCreation(error.to_string()))?;
+    apply_texture_options(&mut texture, options)?;
+
+    Ok(texture)
+}
+
+/// Bytes per row of tightly packed `format` pixels. Indexed formats below 8 bits and
+/// planar YUV formats don't store whole pixels one after another and are rejected, as is
+/// `Unknown`.
+fn packed_pitch(format: PixelFormatEnum, width: u32) -> Result<usize, RendererError> {
+    if matches!(
+        format,
+        PixelFormatEnum::Unknown
+            | PixelFormatEnum::Index1LSB
+            | PixelFormatEnum::Index1MSB
+            | PixelFormatEnum::Index4LSB
+            | PixelFormatEnum::Index4MSB
+            | PixelFormatEnum::YV12
+            | PixelFormatEnum::IYUV
+            | PixelFormatEnum::NV12
+            | PixelFormatEnum::NV21
+    ) {
+        return Err(RendererError::InvalidPixelData(format!(
+            "{:?} is not a packed pixel format",
+            format
+        )));
+    }
+    Ok(format.byte_size_of_pixels(width as usize))
+}
+
+/// Estimated GPU memory held by a texture, based on its size and pixel format.
+pub(crate) fn texture_size_bytes(texture: &Texture) -> usize {
+    let query = texture.query();
+    query.format.byte_size_of_pixels(query.width as usize) * query.height as usize
+}
+
+fn apply_texture_options(
+    texture: &mut Texture,
+    options: TextureOptions,
+) -> Result<(), RendererError> {
+    texture.set_blend_mode(options.blend_mode);
+    set_scale_mode(texture, options.scale_mode)
+}
+
+/// The vertices from a command's offset on and the indices it draws, after checking that
+/// every index refers to one of those vertices.
+fn command_slices<'v, 'i>(
+    vtx_buffer: &'v [imgui::DrawVert],
+    idx_buffer: &'i [imgui::DrawIdx],
+    count: usize,
+    cmd_params: &imgui::DrawCmdParams,
+) -> Result<(&'v [imgui::DrawVert], &'i [imgui::DrawIdx]), RendererError> {
+    let vertices = vtx_buffer.get(cmd_params.vtx_offset..).ok_or_else(|| {
+        RendererError::InvalidDrawData(format!(
+            "vertex offset {} is past the end of a {} vertex buffer",
+            cmd_params.vtx_offset,
+            vtx_buffer.len()
+        ))
+    })?;
+    let indices = idx_buffer
+        .get(cmd_params.idx_offset..)
+        .and_then(|indices| indices.get(..count))
+        .ok_or_else(|| {
+            RendererError::InvalidDrawData(format!(
+                "{} indices at offset {} exceed a {} index buffer",
+                count,
+                cmd_params.idx_offset,
+                idx_buffer.len()
+            ))
+        })?;
+    if let Some(&index) = indices
+        .iter()
+        .find(|&&index| index as usize >= vertices.len())
+    {
+        return Err(RendererError::InvalidDrawData(format!(
+            "index {} is past the end of {} vertices at offset {}",
+            index,
+            vertices.len(),
+            cmd_params.vtx_offset
+        )));
+    }
+    Ok((vertices, indices))
+}
+
+/// Reads a vertex color as RGBA bytes. `DrawVert::col` holds the `IM_COL32` value
+/// Dear ImGui packs with red in the lowest byte, so its bytes are reversed on
+/// big-endian targets. Builds with `IMGUI_USE_BGRA_PACKED_COLOR` aren't supported.
+fn vertex_rgba(vertex: &imgui::DrawVert) -> [u8; 4] {
+    let col = vertex.col;
+    if cfg!(target_endian = "big") {
+        [col[3], col[2], col[1], col[0]]
+    } else {
+        col
+    }
+}
+
+fn premultiply(channel: u8, alpha: u8) -> u8 {
+    ((channel as u16 * alpha as u16 + 127) / 255) as u8
+}
+
+// Declared here rather than taken from `sdl2::sys`, whose `SDL_BlendMode` is a Rust enum
+// that cannot hold a composed custom mode.
+extern "C" {
+    #[link_name = "SDL_ComposeCustomBlendMode"]
+    fn compose_custom_blend_mode(
+        src_color_factor: SDL_BlendFactor,
+        dst_color_factor: SDL_BlendFactor,
+        color_operation: SDL_BlendOperation,
+        src_alpha_factor: SDL_BlendFactor,
+        dst_alpha_factor: SDL_BlendFactor,
+        alpha_operation: SDL_BlendOperation,
+    ) -> u32;
+    #[link_name = "SDL_SetTextureBlendMode"]
+    fn set_texture_custom_blend_mode(texture: *mut SDL_Texture, blend_mode: u32) -> c_int;
+    #[link_name = "SDL_GetTextureBlendMode"]
+    fn get_texture_custom_blend_mode(texture: *mut SDL_Texture, blend_mode: *mut u32) -> c_int;
+    #[link_name = "SDL_SetRenderDrawBlendMode"]
+    fn set_render_draw_custom_blend_mode(renderer: *mut SDL_Renderer, blend_mode: u32) -> c_int;
+    #[link_name = "SDL_GetRenderDrawBlendMode"]
+    fn get_render_draw_custom_blend_mode(
+        renderer: *mut SDL_Renderer,
+        blend_mode: *mut u32,
+    ) -> c_int;
+}
+
+/// Error for a function the loaded SDL lacks, which SDL 2.0.`patch` introduced.
+fn unsupported_sdl_version(patch: u8) -> RendererError {
+    RendererError::UnsupportedSdlVersion {
+        linked: sdl2::version::version(),
+        required: sdl2::version::Version {
+            major: 2,
+            minor: 0,
+            patch,
+        },
+    }
+}
+
+fn set_premultiplied_blend_mode(texture: &Texture) -> Result<(), RendererError> {
+    unsafe {
+        let blend_mode = compose_custom_blend_mode(
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE,
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE_MINUS_SRC_ALPHA,
+            SDL_BlendOperation::SDL_BLENDOPERATION_ADD,
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE,
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE_MINUS_SRC_ALPHA,
+            SDL_BlendOperation::SDL_BLENDOPERATION_ADD,
+        );
+        if set_texture_custom_blend_mode(texture.raw(), blend_mode) != 0 {
+            return Err(sdl_call_error("SDL_SetTextureBlendMode"));
+        }
+    }
+    Ok(())
+}
+
+fn sdl_call_error(call: &'static str) -> RendererError {
+    RendererError::SdlCall {
+        call,
+        message: sdl2::get_error(),
+    }
+}
+
+/// Before SDL 2.0.12 textures keep the filtering `SDL_HINT_RENDER_SCALE_QUALITY` selected
+/// when they were created, so this does nothing there.
+fn set_scale_mode(texture: &Texture, scale_mode: ScaleMode) -> Result<(), RendererError> {
+    let set_texture_scale_mode = match dynamic::optional_functions().set_texture_scale_mode {
+        Some(function) => function,
+        None => return Ok(()),
+    };
+    if unsafe { set_texture_scale_mode(texture.raw(), scale_mode.into()) } != 0 {
+        return Err(sdl_call_error("SDL_SetTextureScaleMode"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{params, vertices};
+
+    #[test]
+    fn grayscale_mapping_expands_to_rgba() {
+        assert_eq!(GrayscaleMapping::Replicate.expand(7), [7, 7, 7, 255]);
+        assert_eq!(
+            GrayscaleMapping::WhiteWithAlpha.expand(7),
+            [255, 255, 255, 7]
+        );
+        assert_eq!(GrayscaleMapping::ReplicateWithAlpha.expand(7), [7; 4]);
+    }
+
+    #[test]
+    fn premultiply_rounds_to_nearest() {
+        assert_eq!(premultiply(255, 255), 255);
+        assert_eq!(premultiply(200, 0), 0);
+        assert_eq!(premultiply(255, 128), 128);
+        assert_eq!(premultiply(128, 128), 64);
+        assert_eq!(premultiply(1, 128), 1);
+    }
+
+    #[test]
+    fn command_slices_start_at_the_offsets() {
+        let vtx_buffer = vertices(5);
+        let idx_buffer = [0, 1, 2, 0, 1, 2, 2];
+        let (vertices, indices) =
+            command_slices(&vtx_buffer, &idx_buffer, 3, &params(2, 3)).unwrap();
+        assert_eq!(vertices.len(), 3);
+        assert_eq!(vertices[0].pos, [2.0, 0.0]);
+        assert_eq!(indices, [0, 1, 2]);
+    }
+
+    #[test]
+    fn command_slices_allow_commands_ending_at_the_buffer_ends() {
+        let vtx_buffer = vertices(3);
+        let idx_buffer = [0, 1, 2];
+        assert!(command_slices(&vtx_buffer, &idx_buffer, 3, &params(0, 0)).is_ok());
+        let (vertices, indices) =
+            command_slices(&vtx_buffer, &idx_buffer, 0, &params(3, 3)).unwrap();
+        assert!(vertices.is_empty() && indices.is_empty());
+    }
+
+    #[test]
+    fn command_slices_reject_out_of_range_offsets_and_counts() {
+        let vtx_buffer = vertices(3);
+        let idx_buffer = [0, 1, 2];
+        for (count, cmd_params) in [
+            (0, params(4, 0)),
+            (0, params(0, 4)),
+            (3, params(0, 1)),
+            (usize::MAX, params(0, 0)),
+        ] {
+            assert!(matches!(
+                command_slices(&vtx_buffer, &idx_buffer, count, &cmd_params),
+                Err(RendererError::InvalidDrawData(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn command_slices_check_indices_against_the_offset_vertices() {
+        let vtx_buffer = vertices(4);
+        let idx_buffer = [0, 1, 2];
+        assert!(command_slices(&vtx_buffer, &idx_buffer, 3, &params(1, 0)).is_ok());
+        assert!(matches!(
+            command_slices(&vtx_buffer, &idx_buffer, 3, &params(2, 0)),
+            Err(RendererError::InvalidDrawData(_))
+        ));
+    }
+
+    #[test]
+    fn clip_rounding_rounds_each_corner() {
+        let round = |rounding: ClipRounding, value: f32| {
+            (rounding.round_min(value), rounding.round_max(value))
+        };
+        assert_eq!(round(ClipRounding::Truncate, 1.75), (1.0, 1.0));
+        assert_eq!(round(ClipRounding::Outward, 1.25), (1.0, 2.0));
+        assert_eq!(round(ClipRounding::Outward, 3.0), (3.0, 3.0));
+        assert_eq!(round(ClipRounding::Nearest, 1.5), (2.0, 2.0));
+        assert_eq!(round(ClipRounding::Nearest, 2.5), (2.0, 2.0));
+        assert_eq!(round(ClipRounding::Nearest, 2.25), (2.0, 2.0));
+    }
+
+    fn frame(width: f32, height: f32) -> FrameGeometry {
+        FrameGeometry::new([0.0, 0.0], [width, height], [1.0, 1.0]).unwrap()
+    }
+
+    #[test]
+    fn frame_geometry_rejects_empty_framebuffers() {
+        assert!(FrameGeometry::new([0.0, 0.0], [0.0, 100.0], [1.0, 1.0]).is_none());
+        assert!(FrameGeometry::new([0.0, 0.0], [100.0, 100.0], [-1.0, 1.0]).is_none());
+        assert!(FrameGeometry::new([0.0, 0.0], [f32::NAN, 100.0], [1.0, 1.0]).is_none());
+    }
+
+    #[test]
+    fn frame_geometry_transforms_display_to_output_pixels() {
+        let frame = FrameGeometry::new([100.0, 50.0], [200.0, 100.0], [2.0, 2.0]).unwrap();
+        assert!(frame.transforms_vertices());
+        assert_eq!(frame.transform([100.0, 50.0]), [0.0, 0.0]);
+        assert_eq!(frame.transform([110.25, 60.0]), [20.5, 20.0]);
+    }
+
+    #[test]
+    fn frame_geometry_skips_the_identity_transform() {
+        let frame = frame(200.0, 100.0);
+        assert!(!frame.transforms_vertices());
+        assert_eq!(frame.transform([12.5, 7.25]), [12.5, 7.25]);
+    }
+
+    #[test]
+    fn render_state_reset_leaves_a_plain_canvas_alone() {
+        assert_eq!(
+            RenderStateReset::for_canvas((0, 0), (1.0, 1.0)),
+            RenderStateReset {
+                clear_logical_size: false,
+                reset_scale: false,
+            }
+        );
+    }
+
+    #[test]
+    fn render_state_reset_resets_an_application_scale() {
+        assert_eq!(
+            RenderStateReset::for_canvas((0, 0), (2.0, 0.5)),
+            RenderStateReset {
+                clear_logical_size: false,
+                reset_scale: true,
+            }
+        );
+    }
+
+    #[test]
+    fn render_state_reset_clears_a_logical_size_and_its_scale() {
+        // SDL derives the scale from the logical size, and keeps 1 for a matching window.
+        for scale in [(1.0, 1.0), (1.5, 1.5)] {
+            assert_eq!(
+                RenderStateReset::for_canvas((640, 480), scale),
+                RenderStateReset {
+                    clear_logical_size: true,
+                    reset_scale: true,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn frame_geometry_snaps_to_whole_pixels() {
+        let mut frame = frame(200.0, 100.0);
+        frame.snap_to_pixels = true;
+        assert!(frame.transforms_vertices());
+        assert_eq!(frame.transform([12.5, 7.25]), [13.0, 7.0]);
+    }
+
+    #[test]
+    fn vertex_rgba_reads_im_col32_bytes() {
+        let vertex = imgui::DrawVert {
+            pos: [0.0, 0.0],
+            uv: [0.0, 0.0],
+            col: 0x8040_2010u32.to_ne_bytes(),
+        };
+        assert_eq!(vertex_rgba(&vertex), [0x10, 0x20, 0x40, 0x80]);
+    }
+
+    #[test]
+    fn frame_geometry_offsets_by_the_origin() {
+        let mut frame = FrameGeometry::new([100.0, 50.0], [200.0, 100.0], [2.0, 2.0]).unwrap();
+        frame.origin = [5.0, 7.0];
+        assert_eq!(frame.transform([100.0, 50.0]), [5.0, 7.0]);
+
+        // Snapping happens after the offset.
+        frame.snap_to_pixels = true;
+        frame.origin = [5.25, 7.0];
+        assert_eq!(frame.transform([110.25, 60.0]), [26.0, 27.0]);
+    }
+
+    #[test]
+    fn letterbox_rect_centers_the_content() {
+        let rect = letterbox_rect(Rect::new(0, 0, 1920, 1080), 4, 3);
+        assert_eq!(
+            (rect.x(), rect.y(), rect.width(), rect.height()),
+            (240, 0, 1440, 1080)
+        );
+
+        let rect = letterbox_rect(Rect::new(10, 20, 800, 800), 16, 9);
+        assert_eq!(
+            (rect.x(), rect.y(), rect.width(), rect.height()),
+            (10, 195, 800, 450)
+        );
+    }
+
+    #[test]
+    fn integer_scale_viewport_uses_the_largest_whole_scale() {
+        let (viewport, scale) = integer_scale_viewport((1920, 1080), (320, 240)).unwrap();
+        assert_eq!(scale, 4);
+        assert_eq!(
+            (
+                viewport.x(),
+                viewport.y(),
+                viewport.width(),
+                viewport.height()
+            ),
+            (320, 60, 1280, 960)
+        );
+    }
+
+    #[test]
+    fn integer_scale_viewport_overhangs_small_windows() {
+        let (viewport, scale) = integer_scale_viewport((300, 200), (320, 240)).unwrap();
+        assert_eq!(scale, 1);
+        assert_eq!((viewport.x(), viewport.y()), (-10, -20));
+        assert!(integer_scale_viewport((300, 200), (0, 240)).is_none());
+    }
+
+    #[test]
+    fn letterbox_rect_keeps_the_area_for_empty_content() {
+        let area = Rect::new(10, 20, 300, 200);
+        assert_eq!(letterbox_rect(area, 0, 3), area);
+        assert_eq!(letterbox_rect(area, 4, 0), area);
+    }
+
+    #[test]
+    fn frame_geometry_orients_corners_for_every_rotation() {
+        let mut frame = frame(200.0, 100.0);
+        let corners =
+            |frame: &FrameGeometry| [[0.0, 0.0], [200.0, 100.0]].map(|corner| frame.orient(corner));
+
+        assert_eq!(corners(&frame), [[0.0, 0.0], [200.0, 100.0]]);
+        frame.rotation = OutputRotation::Rotate90;
+        assert_eq!(corners(&frame), [[100.0, 0.0], [0.0, 200.0]]);
+        frame.rotation = OutputRotation::Rotate180;
+        assert_eq!(corners(&frame), [[200.0, 100.0], [0.0, 0.0]]);
+        frame.rotation = OutputRotation::Rotate270;
+        assert_eq!(corners(&frame), [[0.0, 200.0], [100.0, 0.0]]);
+    }
+
+    #[test]
+    fn frame_geometry_mirrors_within_the_rotated_output() {
+        let mut frame = frame(200.0, 100.0);
+        frame.mirror = [true, false];
+        assert!(frame.transforms_vertices());
+        assert_eq!(frame.orient([10.0, 20.0]), [190.0, 20.0]);
+
+        // A quarter turn makes the output 100 pixels wide.
+        frame.rotation = OutputRotation::Rotate90;
+        assert_eq!(frame.orient([10.0, 20.0]), [20.0, 10.0]);
+        frame.mirror = [false, true];
+        assert_eq!(frame.orient([10.0, 20.0]), [80.0, 190.0]);
+    }
+
+    fn source(width: u32, height: u32) -> TextureSource {
+        TextureSource {
+            format: PixelFormatEnum::RGBA32,
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+            options: TextureOptions::default(),
+        }
+    }
+
+    #[test]
+    fn texture_source_mirrors_full_and_partial_updates() {
+        let mut source = source(2, 2);
+        source.update(None, &[1; 16], 8).unwrap();
+        assert_eq!(source.pixels, [1; 16]);
+
+        // One pixel per row, read from rows padded to 12 bytes.
+        let pixels = [[2; 4], [9; 4], [9; 4], [3; 4]].concat();
+        source
+            .update(Some(Rect::new(1, 0, 1, 2)), &pixels, 12)
+            .unwrap();
+        assert_eq!(source.pixels, [[1; 4], [2; 4], [1; 4], [3; 4]].concat());
+    }
+
+    #[test]
+    fn texture_source_clips_updates_like_sdl() {
+        let mut source = source(2, 2);
+        source
+            .update(Some(Rect::new(1, 1, 4, 4)), &[5; 16], 16)
+            .unwrap();
+        assert_eq!(source.pixels, [[0; 4], [0; 4], [0; 4], [5; 4]].concat());
+
+        source
+            .update(Some(Rect::new(3, 3, 1, 1)), &[7; 4], 4)
+            .unwrap();
+        assert_eq!(source.pixels, [[0; 4], [0; 4], [0; 4], [5; 4]].concat());
+    }
+
+    #[test]
+    fn texture_source_rejects_short_pixel_data() {
+        let mut source = source(2, 2);
+        assert!(matches!(
+            source.update(None, &[1; 12], 8),
+            Err(RendererError::InvalidPixelData(_))
+        ));
+        assert!(source.update(None, &[1; 16], 4).is_err());
+        assert_eq!(source.pixels, [0; 16]);
+    }
+
+    #[test]
+    fn texture_ids_report_unregistered_ids_as_stale() {
+        let mut texture_ids = TextureIds::new();
+        let first = texture_ids.issue();
+        let second = texture_ids.issue();
+        assert!(texture_ids.remove(first));
+        assert!(!texture_ids.remove(first));
+
+        assert!(texture_ids.is_stale(first));
+        assert!(!texture_ids.is_stale(second));
+        assert!(!texture_ids.is_stale(imgui::TextureId::new(second.id() + 1)));
+
+        // A later registration gets a fresh id, so the old one stays stale.
+        let third = texture_ids.issue();
+        assert!(third != first && third != second);
+        assert!(texture_ids.is_stale(first));
+        assert_eq!(texture_ids.iter().collect::<Vec<_>>(), [second, third]);
     }
 }
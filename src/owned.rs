@@ -0,0 +1,69 @@
+//! Draw data copied out of imgui so it can be rendered later or on another thread.
+
+/// A snapshot of `imgui::DrawData` that owns its vertex, index and command buffers.
+/// imgui 0.8 has no owned draw data of its own, so this stands in for it.
+///
+/// User callbacks (`DrawCmd::RawCallback`) point into the live draw lists and are
+/// dropped when the snapshot is taken.
+#[derive(Clone, Debug)]
+pub struct OwnedDrawData {
+    pub(crate) display_pos: [f32; 2],
+    pub(crate) display_size: [f32; 2],
+    pub(crate) framebuffer_scale: [f32; 2],
+    pub(crate) draw_lists: Vec<OwnedDrawList>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct OwnedDrawList {
+    pub(crate) vtx_buffer: Vec<imgui::DrawVert>,
+    pub(crate) idx_buffer: Vec<imgui::DrawIdx>,
+    pub(crate) commands: Vec<OwnedDrawCmd>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum OwnedDrawCmd {
+    Elements {
+        count: usize,
+        cmd_params: imgui::DrawCmdParams,
+    },
+    ResetRenderState,
+}
+
+impl OwnedDrawData {
+    pub fn new(draw_data: &imgui::DrawData) -> Self {
+        let draw_lists = draw_data
+            .draw_lists()
+            .map(|draw_list| OwnedDrawList {
+                vtx_buffer: draw_list.vtx_buffer().to_vec(),
+                idx_buffer: draw_list.idx_buffer().to_vec(),
+                commands: draw_list
+                    .commands()
+                    .filter_map(|command| match command {
+                        imgui::DrawCmd::Elements { count, cmd_params } => {
+                            Some(OwnedDrawCmd::Elements { count, cmd_params })
+                        }
+                        imgui::DrawCmd::ResetRenderState => Some(OwnedDrawCmd::ResetRenderState),
+                        imgui::DrawCmd::RawCallback { .. } => None,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            display_pos: draw_data.display_pos,
+            display_size: draw_data.display_size,
+            framebuffer_scale: draw_data.framebuffer_scale,
+            draw_lists,
+        }
+    }
+
+    pub fn display_size(&self) -> [f32; 2] {
+        self.display_size
+    }
+}
+
+impl From<&imgui::DrawData> for OwnedDrawData {
+    fn from(draw_data: &imgui::DrawData) -> Self {
+        Self::new(draw_data)
+    }
+}
@@ -0,0 +1,173 @@
+//! Renderer that owns its canvas and texture creator, for storing next to other app state.
+
+use std::mem::ManuallyDrop;
+use std::rc::Rc;
+
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+use crate::{
+    reset_imgui_context, OwnedDrawData, RenderOutcome, Renderer, RendererError, TextureSource,
+};
+
+/// A [`Renderer`] bundled with the canvas it draws to and the texture creator its
+/// textures borrow from, so it can live in a plain `struct App { ui: OwnedRenderer, .. }`.
+///
+/// The texture creator is reference counted, so the application can keep its own
+/// handle to it (see [`OwnedRenderer::with_texture_creator`]) instead of borrowing it.
+///
+/// The common texture creation helpers are mirrored here and use the owned creator.
+/// Everything else goes through [`OwnedRenderer::with_renderer`], which hands out the
+/// inner renderer for a lifetime that can't escape the closure.
+pub struct OwnedRenderer {
+    // Dropped by hand before `texture_creator`, which it borrows from.
+    renderer: ManuallyDrop<Renderer<'static>>,
+    texture_creator: Rc<TextureCreator<WindowContext>>,
+    canvas: WindowCanvas,
+}
+
+impl OwnedRenderer {
+    pub fn new(
+        canvas: WindowCanvas,
+        imgui_context: &mut imgui::Context,
+    ) -> Result<Self, RendererError> {
+        let texture_creator = Rc::new(canvas.texture_creator());
+        Self::with_texture_creator(canvas, imgui_context, texture_creator)
+    }
+
+    /// Like [`OwnedRenderer::new`], but shares a texture creator the application already
+    /// holds. It must belong to `canvas`.
+    pub fn with_texture_creator(
+        mut canvas: WindowCanvas,
+        imgui_context: &mut imgui::Context,
+        texture_creator: Rc<TextureCreator<WindowContext>>,
+    ) -> Result<Self, RendererError> {
+        // The `Rc` keeps the creator at a fixed address and is only released after the
+        // renderer in `drop`.
+        let creator_ref = unsafe { &*Rc::as_ptr(&texture_creator) };
+        let renderer = Renderer::new(&mut canvas, imgui_context, creator_ref)?;
+
+        Ok(Self {
+            renderer: ManuallyDrop::new(renderer),
+            texture_creator,
+            canvas,
+        })
+    }
+
+    /// Drops the renderer, its textures and the canvas, and undoes the state the renderer
+    /// set on `imgui_context`. See [`Renderer::destroy`].
+    pub fn destroy(self, imgui_context: &mut imgui::Context) {
+        drop(self);
+        reset_imgui_context(imgui_context);
+    }
+
+    /// See [`Renderer::begin_frame`].
+    pub fn begin_frame(&mut self) {
+        self.renderer.begin_frame();
+    }
+
+    pub fn render(&mut self, draw_data: &imgui::DrawData) -> Result<RenderOutcome, RendererError> {
+        self.renderer.render(&mut self.canvas, draw_data)
+    }
+
+    pub fn render_owned(
+        &mut self,
+        draw_data: &OwnedDrawData,
+    ) -> Result<RenderOutcome, RendererError> {
+        self.renderer.render_owned(&mut self.canvas, draw_data)
+    }
+
+    /// See [`Renderer::create_texture_rgba`].
+    pub fn create_texture_rgba(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<imgui::TextureId, RendererError> {
+        self.with_renderer(|renderer, texture_creator, _| {
+            renderer.create_texture_rgba(texture_creator, width, height, pixels)
+        })
+    }
+
+    /// See [`Renderer::load_texture_file`].
+    #[cfg(feature = "image")]
+    pub fn load_texture_file<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<imgui::TextureId, RendererError> {
+        self.with_renderer(|renderer, texture_creator, _| {
+            renderer.load_texture_file(texture_creator, path)
+        })
+    }
+
+    /// See [`Renderer::create_streaming_texture`].
+    pub fn create_streaming_texture(
+        &mut self,
+        format: PixelFormatEnum,
+        width: u32,
+        height: u32,
+    ) -> Result<imgui::TextureId, RendererError> {
+        self.with_renderer(|renderer, texture_creator, _| {
+            renderer.create_streaming_texture(texture_creator, format, width, height)
+        })
+    }
+
+    /// See [`Renderer::create_retained_texture`].
+    pub fn create_retained_texture(
+        &mut self,
+        source: TextureSource,
+    ) -> Result<imgui::TextureId, RendererError> {
+        self.with_renderer(|renderer, texture_creator, _| {
+            renderer.create_retained_texture(texture_creator, source)
+        })
+    }
+
+    /// See [`Renderer::recreate_textures`].
+    pub fn recreate_textures(
+        &mut self,
+        imgui_context: &mut imgui::Context,
+    ) -> Result<(), RendererError> {
+        self.with_renderer(|renderer, texture_creator, _| {
+            renderer.recreate_textures(imgui_context, texture_creator)
+        })
+    }
+
+    /// Runs `f` with the inner renderer, its texture creator and the canvas, e.g. to
+    /// register or update textures.
+    pub fn with_renderer<F, R>(&mut self, f: F) -> R
+    where
+        F: for<'r> FnOnce(
+            &mut Renderer<'r>,
+            &'r TextureCreator<WindowContext>,
+            &mut WindowCanvas,
+        ) -> R,
+    {
+        let renderer: *mut Renderer<'static> = &mut *self.renderer;
+        // Shortening the lifetime keeps textures created inside `f` from outliving `self`.
+        let renderer = unsafe { &mut *renderer.cast::<Renderer<'_>>() };
+        f(renderer, &self.texture_creator, &mut self.canvas)
+    }
+
+    pub fn renderer(&self) -> &Renderer<'_> {
+        &self.renderer
+    }
+
+    pub fn texture_creator(&self) -> &Rc<TextureCreator<WindowContext>> {
+        &self.texture_creator
+    }
+
+    pub fn canvas(&self) -> &WindowCanvas {
+        &self.canvas
+    }
+
+    pub fn canvas_mut(&mut self) -> &mut WindowCanvas {
+        &mut self.canvas
+    }
+}
+
+impl Drop for OwnedRenderer {
+    fn drop(&mut self) {
+        unsafe { ManuallyDrop::drop(&mut self.renderer) };
+    }
+}
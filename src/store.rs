@@ -0,0 +1,34 @@
+//! Texture lookup used while rendering, so applications can keep their own textures.
+
+use sdl2::render::Texture;
+
+use crate::Renderer;
+
+/// Resolves the texture ids found in draw data to SDL textures. Pass an implementor to
+/// [`Renderer::render_with_store`] to draw textures the application already owns without
+/// registering them with the renderer.
+pub trait TextureStore {
+    fn texture(&self, texture_id: imgui::TextureId) -> Option<&Texture<'_>>;
+
+    /// Whether `texture_id` used to be valid but has been removed; only affects which
+    /// error [`crate::MissingTexturePolicy::Error`] reports.
+    fn is_stale(&self, _texture_id: imgui::TextureId) -> bool {
+        false
+    }
+}
+
+impl TextureStore for imgui::Textures<Texture<'_>> {
+    fn texture(&self, texture_id: imgui::TextureId) -> Option<&Texture<'_>> {
+        self.get(texture_id)
+    }
+}
+
+impl TextureStore for Renderer<'_> {
+    fn texture(&self, texture_id: imgui::TextureId) -> Option<&Texture<'_>> {
+        self.texture_map.get(texture_id)
+    }
+
+    fn is_stale(&self, texture_id: imgui::TextureId) -> bool {
+        self.is_texture_stale(texture_id)
+    }
+}
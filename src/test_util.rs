@@ -0,0 +1,22 @@
+//! Draw data fixtures shared by the unit tests.
+
+/// `count` vertices at `(index, 0)` sampling the middle of the texture.
+pub(crate) fn vertices(count: usize) -> Vec<imgui::DrawVert> {
+    (0..count)
+        .map(|index| imgui::DrawVert {
+            pos: [index as f32, 0.0],
+            uv: [0.5, 0.5],
+            col: [255; 4],
+        })
+        .collect()
+}
+
+/// Command parameters with a 100x100 clip rect and the given offsets.
+pub(crate) fn params(vtx_offset: usize, idx_offset: usize) -> imgui::DrawCmdParams {
+    imgui::DrawCmdParams {
+        clip_rect: [0.0, 0.0, 100.0, 100.0],
+        texture_id: imgui::TextureId::new(1),
+        vtx_offset,
+        idx_offset,
+    }
+}
@@ -0,0 +1,347 @@
+//! Optional checks for draw data that is well formed but almost certainly wrong.
+
+use std::fmt::{self, Display};
+
+use crate::owned::{OwnedDrawCmd, OwnedDrawData};
+
+/// Texture coordinates further than this outside `[0, 1]` are reported. SDL clamps them,
+/// so such values usually come from a division by a tiny size in widget code.
+const UV_SLACK: f32 = 1.0;
+
+/// One suspicious draw command found by [`validate_draw_data`] or
+/// [`OwnedDrawData::validate`]; `draw_list` and `command` index into the draw data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawDataIssue {
+    pub draw_list: usize,
+    pub command: usize,
+    pub kind: DrawDataIssueKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawDataIssueKind {
+    /// The index or vertex range of the command doesn't fit its draw list's buffers.
+    OutOfBounds,
+    /// The index count isn't a multiple of three.
+    PartialTriangle { count: usize },
+    /// A vertex position is NaN or infinite; `vertex` is relative to the command's offset.
+    NonFinitePosition { vertex: usize, pos: [f32; 2] },
+    /// A texture coordinate is NaN, infinite or far outside `[0, 1]`.
+    UvOutOfRange { vertex: usize, uv: [f32; 2] },
+    /// The clip rect is NaN or encloses no area, so the command never draws anything.
+    EmptyClipRect { clip_rect: [f32; 4] },
+}
+
+impl DrawDataIssueKind {
+    /// Whether the command can't be drawn as intended. Out-of-range texture coordinates
+    /// and empty clip rects are legal, if suspicious: SDL clamps the former and the
+    /// renderer skips commands with the latter.
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            Self::OutOfBounds | Self::PartialTriangle { .. } | Self::NonFinitePosition { .. }
+        )
+    }
+}
+
+impl Display for DrawDataIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds => write!(f, "Indices or vertices are out of bounds"),
+            Self::PartialTriangle { count } => {
+                write!(f, "{} indices don't form whole triangles", count)
+            }
+            Self::NonFinitePosition { vertex, pos } => {
+                write!(f, "Vertex {} has position {:?}", vertex, pos)
+            }
+            Self::UvOutOfRange { vertex, uv } => {
+                write!(f, "Vertex {} has texture coordinates {:?}", vertex, uv)
+            }
+            Self::EmptyClipRect { clip_rect } => write!(f, "Clip rect {:?} is empty", clip_rect),
+        }
+    }
+}
+
+impl Display for DrawDataIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Command {} of draw list {}: {}",
+            self.command, self.draw_list, self.kind
+        )
+    }
+}
+
+/// Checks every draw command for NaN or infinite positions, texture coordinates far
+/// outside the texture, empty clip rects and index counts that don't match the buffers.
+/// Meant for debugging custom widgets; see also [`crate::Renderer::set_validate_draw_data`].
+pub fn validate_draw_data(draw_data: &imgui::DrawData) -> Vec<DrawDataIssue> {
+    let mut issues = Vec::new();
+    for (list_index, draw_list) in draw_data.draw_lists().enumerate() {
+        for (command_index, command) in draw_list.commands().enumerate() {
+            if let imgui::DrawCmd::Elements { count, cmd_params } = command {
+                issues.extend(
+                    check_elements(
+                        draw_list.vtx_buffer(),
+                        draw_list.idx_buffer(),
+                        count,
+                        &cmd_params,
+                    )
+                    .into_iter()
+                    .map(|kind| DrawDataIssue {
+                        draw_list: list_index,
+                        command: command_index,
+                        kind,
+                    }),
+                );
+            }
+        }
+    }
+    issues
+}
+
+impl OwnedDrawData {
+    /// Like [`validate_draw_data`], for a snapshot.
+    pub fn validate(&self) -> Vec<DrawDataIssue> {
+        let mut issues = Vec::new();
+        for (list_index, draw_list) in self.draw_lists.iter().enumerate() {
+            for (command_index, command) in draw_list.commands.iter().enumerate() {
+                if let OwnedDrawCmd::Elements { count, cmd_params } = command {
+                    issues.extend(
+                        check_elements(
+                            &draw_list.vtx_buffer,
+                            &draw_list.idx_buffer,
+                            *count,
+                            cmd_params,
+                        )
+                        .into_iter()
+                        .map(|kind| DrawDataIssue {
+                            draw_list: list_index,
+                            command: command_index,
+                            kind,
+                        }),
+                    );
+                }
+            }
+        }
+        issues
+    }
+}
+
+/// Reports at most one issue of each kind per command.
+pub(crate) fn check_elements(
+    vtx_buffer: &[imgui::DrawVert],
+    idx_buffer: &[imgui::DrawIdx],
+    count: usize,
+    cmd_params: &imgui::DrawCmdParams,
+) -> Vec<DrawDataIssueKind> {
+    let mut issues = Vec::new();
+
+    let [min_x, min_y, max_x, max_y] = cmd_params.clip_rect;
+    if !(max_x > min_x && max_y > min_y) {
+        issues.push(DrawDataIssueKind::EmptyClipRect {
+            clip_rect: cmd_params.clip_rect,
+        });
+    }
+    if !count.is_multiple_of(3) {
+        issues.push(DrawDataIssueKind::PartialTriangle { count });
+    }
+
+    let vertices = vtx_buffer.get(cmd_params.vtx_offset..);
+    let indices = idx_buffer
+        .get(cmd_params.idx_offset..)
+        .and_then(|indices| indices.get(..count));
+    let (vertices, indices) = match (vertices, indices) {
+        (Some(vertices), Some(indices)) => (vertices, indices),
+        _ => {
+            issues.push(DrawDataIssueKind::OutOfBounds);
+            return issues;
+        }
+    };
+
+    let mut out_of_bounds = false;
+    let mut bad_position = None;
+    let mut bad_uv = None;
+    for &index in indices {
+        let vertex = index as usize;
+        let vert = match vertices.get(vertex) {
+            Some(vert) => vert,
+            None => {
+                out_of_bounds = true;
+                continue;
+            }
+        };
+
+        if bad_position.is_none() && !vert.pos.iter().all(|value| value.is_finite()) {
+            bad_position = Some(DrawDataIssueKind::NonFinitePosition {
+                vertex,
+                pos: vert.pos,
+            });
+        }
+        if bad_uv.is_none()
+            && !vert
+                .uv
+                .iter()
+                .all(|value| (-UV_SLACK..=1.0 + UV_SLACK).contains(value))
+        {
+            bad_uv = Some(DrawDataIssueKind::UvOutOfRange {
+                vertex,
+                uv: vert.uv,
+            });
+        }
+    }
+
+    if out_of_bounds {
+        issues.push(DrawDataIssueKind::OutOfBounds);
+    }
+    issues.extend(bad_position);
+    issues.extend(bad_uv);
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{params, vertices};
+
+    #[test]
+    fn accepts_well_formed_commands() {
+        let vtx_buffer = vertices(4);
+        let idx_buffer = [0, 1, 2, 0, 2, 3];
+        assert_eq!(
+            check_elements(&vtx_buffer, &idx_buffer, 6, &params(0, 0)),
+            []
+        );
+    }
+
+    #[test]
+    fn accepts_commands_ending_at_the_buffer_ends() {
+        let vtx_buffer = vertices(6);
+        let idx_buffer = [0, 1, 2, 0, 1, 2];
+        assert_eq!(
+            check_elements(&vtx_buffer, &idx_buffer, 3, &params(3, 3)),
+            []
+        );
+        // An empty command right at the ends touches nothing.
+        assert_eq!(
+            check_elements(&vtx_buffer, &idx_buffer, 0, &params(6, 6)),
+            []
+        );
+    }
+
+    #[test]
+    fn reports_offsets_past_the_buffers() {
+        let vtx_buffer = vertices(3);
+        let idx_buffer = [0, 1, 2];
+        assert_eq!(
+            check_elements(&vtx_buffer, &idx_buffer, 0, &params(4, 0)),
+            [DrawDataIssueKind::OutOfBounds]
+        );
+        assert_eq!(
+            check_elements(&vtx_buffer, &idx_buffer, 0, &params(0, 4)),
+            [DrawDataIssueKind::OutOfBounds]
+        );
+    }
+
+    #[test]
+    fn reports_counts_past_the_index_buffer() {
+        let vtx_buffer = vertices(3);
+        let idx_buffer = [0, 1, 2, 0, 1, 2];
+        assert_eq!(
+            check_elements(&vtx_buffer, &idx_buffer, 6, &params(0, 1)),
+            [DrawDataIssueKind::OutOfBounds]
+        );
+    }
+
+    #[test]
+    fn resolves_indices_relative_to_the_vertex_offset() {
+        let vtx_buffer = vertices(4);
+        let idx_buffer = [0, 1, 2];
+        assert_eq!(
+            check_elements(&vtx_buffer, &idx_buffer, 3, &params(1, 0)),
+            []
+        );
+        assert_eq!(
+            check_elements(&vtx_buffer, &idx_buffer, 3, &params(2, 0)),
+            [DrawDataIssueKind::OutOfBounds]
+        );
+    }
+
+    #[test]
+    fn reports_partial_triangles() {
+        let vtx_buffer = vertices(3);
+        let idx_buffer = [0, 1, 2, 0];
+        assert_eq!(
+            check_elements(&vtx_buffer, &idx_buffer, 4, &params(0, 0)),
+            [DrawDataIssueKind::PartialTriangle { count: 4 }]
+        );
+    }
+
+    #[test]
+    fn reports_the_first_non_finite_position() {
+        let mut vtx_buffer = vertices(5);
+        vtx_buffer[3].pos = [f32::NAN, 0.0];
+        vtx_buffer[4].pos = [f32::INFINITY, 0.0];
+        let idx_buffer = [0, 1, 2, 0, 1, 2];
+        // NaN doesn't compare equal, so the position is checked separately.
+        let issues = check_elements(&vtx_buffer, &idx_buffer, 3, &params(2, 3));
+        match issues.as_slice() {
+            [DrawDataIssueKind::NonFinitePosition { vertex: 1, pos }] => assert!(pos[0].is_nan()),
+            issues => panic!("unexpected issues {:?}", issues),
+        }
+    }
+
+    #[test]
+    fn allows_uvs_within_the_slack() {
+        let mut vtx_buffer = vertices(3);
+        vtx_buffer[0].uv = [-UV_SLACK, 1.0 + UV_SLACK];
+        assert_eq!(
+            check_elements(&vtx_buffer, &[0, 1, 2], 3, &params(0, 0)),
+            []
+        );
+
+        vtx_buffer[1].uv = [0.0, 2.5];
+        assert_eq!(
+            check_elements(&vtx_buffer, &[0, 1, 2], 3, &params(0, 0)),
+            [DrawDataIssueKind::UvOutOfRange {
+                vertex: 1,
+                uv: [0.0, 2.5],
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_empty_and_nan_clip_rects() {
+        let vtx_buffer = vertices(3);
+        for clip_rect in [[10.0, 0.0, 10.0, 100.0], [0.0, f32::NAN, 100.0, 100.0]] {
+            let cmd_params = imgui::DrawCmdParams {
+                clip_rect,
+                ..params(0, 0)
+            };
+            let issues = check_elements(&vtx_buffer, &[0, 1, 2], 3, &cmd_params);
+            assert!(matches!(
+                issues.as_slice(),
+                [DrawDataIssueKind::EmptyClipRect { .. }]
+            ));
+        }
+    }
+
+    #[test]
+    fn only_undrawable_issues_are_fatal() {
+        assert!(DrawDataIssueKind::OutOfBounds.is_fatal());
+        assert!(DrawDataIssueKind::PartialTriangle { count: 4 }.is_fatal());
+        assert!(DrawDataIssueKind::NonFinitePosition {
+            vertex: 0,
+            pos: [f32::NAN, 0.0],
+        }
+        .is_fatal());
+        assert!(!DrawDataIssueKind::UvOutOfRange {
+            vertex: 0,
+            uv: [5.0, 0.0],
+        }
+        .is_fatal());
+        assert!(!DrawDataIssueKind::EmptyClipRect {
+            clip_rect: [0.0; 4],
+        }
+        .is_fatal());
+    }
+}
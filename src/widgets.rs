@@ -0,0 +1,142 @@
+//! Drawing helpers for registered textures that go beyond a plain `imgui::Image`.
+
+use crate::Renderer;
+
+/// Image widgets that take a texture's size and flip setting from the [`Renderer`] it
+/// is registered with.
+pub trait UiExt {
+    /// Draws `texture_id` as large as fits into `size` while keeping its aspect ratio.
+    fn image_sdl(&self, renderer: &Renderer, texture_id: imgui::TextureId, size: [f32; 2]);
+
+    /// Like [`UiExt::image_sdl`], but as a button; returns whether it was clicked.
+    fn image_button_sdl(
+        &self,
+        renderer: &Renderer,
+        texture_id: imgui::TextureId,
+        size: [f32; 2],
+    ) -> bool;
+}
+
+impl UiExt for imgui::Ui<'_> {
+    fn image_sdl(&self, renderer: &Renderer, texture_id: imgui::TextureId, size: [f32; 2]) {
+        let (uv0, uv1) = renderer.texture_uvs(texture_id);
+        imgui::Image::new(texture_id, fit_size(renderer, texture_id, size))
+            .uv0(uv0)
+            .uv1(uv1)
+            .build(self);
+    }
+
+    fn image_button_sdl(
+        &self,
+        renderer: &Renderer,
+        texture_id: imgui::TextureId,
+        size: [f32; 2],
+    ) -> bool {
+        let (uv0, uv1) = renderer.texture_uvs(texture_id);
+        imgui::ImageButton::new(texture_id, fit_size(renderer, texture_id, size))
+            .uv0(uv0)
+            .uv1(uv1)
+            .build(self)
+    }
+}
+
+/// Scales the texture's size down or up to fit `size`; unknown textures keep `size`.
+fn fit_size(renderer: &Renderer, texture_id: imgui::TextureId, size: [f32; 2]) -> [f32; 2] {
+    match renderer.texture_info(texture_id) {
+        Some(info) if info.width > 0 && info.height > 0 => {
+            let (width, height) = (info.width as f32, info.height as f32);
+            let scale = (size[0] / width).min(size[1] / height);
+            [width * scale, height * scale]
+        }
+        _ => size,
+    }
+}
+
+/// Most tiles [`tiled_image`] emits for one call; rows beyond it are left out.
+pub const MAX_TILES: usize = 16384;
+
+/// Draws `texture_id` repeated across an area of `size` at the cursor, emitting one
+/// quad per tile since SDL clamps texture coordinates instead of wrapping them.
+/// Tiles cut off at the right and bottom edges are cropped, not squashed. Non-finite
+/// or non-positive sizes draw nothing, and at most [`MAX_TILES`] tiles are drawn.
+pub fn tiled_image(
+    ui: &imgui::Ui,
+    texture_id: imgui::TextureId,
+    size: [f32; 2],
+    tile_size: [f32; 2],
+) {
+    let origin = ui.cursor_screen_pos();
+
+    if let Some((columns, rows)) = tile_counts(size, tile_size) {
+        let draw_list = ui.get_window_draw_list();
+
+        for row in 0..rows {
+            let y = row as f32 * tile_size[1];
+            let height = (size[1] - y).min(tile_size[1]);
+
+            for column in 0..columns {
+                let x = column as f32 * tile_size[0];
+                let width = (size[0] - x).min(tile_size[0]);
+
+                draw_list
+                    .add_image(
+                        texture_id,
+                        [origin[0] + x, origin[1] + y],
+                        [origin[0] + x + width, origin[1] + y + height],
+                    )
+                    .uv_max([width / tile_size[0], height / tile_size[1]])
+                    .build();
+            }
+        }
+    }
+
+    if size.iter().all(|extent| extent.is_finite()) {
+        ui.dummy(size);
+    }
+}
+
+/// Columns and rows of tiles covering `size`, with the rows cut so the total stays
+/// within [`MAX_TILES`]; `None` when there is nothing to draw.
+fn tile_counts(size: [f32; 2], tile_size: [f32; 2]) -> Option<(usize, usize)> {
+    let valid = |extent: f32| extent.is_finite() && extent > 0.0;
+    if !size.into_iter().chain(tile_size).all(valid) {
+        return None;
+    }
+
+    // Float to int casts saturate, so huge ratios clamp instead of wrapping.
+    let columns = ((size[0] / tile_size[0]).ceil() as usize).min(MAX_TILES);
+    let rows = ((size[1] / tile_size[1]).ceil() as usize).min(MAX_TILES / columns);
+    Some((columns, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_counts_round_partial_tiles_up() {
+        assert_eq!(tile_counts([100.0, 50.0], [32.0, 50.0]), Some((4, 1)));
+        assert_eq!(tile_counts([64.0, 0.5], [32.0, 32.0]), Some((2, 1)));
+    }
+
+    #[test]
+    fn tile_counts_reject_unusable_sizes() {
+        for (size, tile_size) in [
+            ([f32::INFINITY, 10.0], [1.0, 1.0]),
+            ([10.0, f32::NAN], [1.0, 1.0]),
+            ([10.0, 10.0], [0.0, 1.0]),
+            ([10.0, 10.0], [1.0, -1.0]),
+            ([0.0, 10.0], [1.0, 1.0]),
+        ] {
+            assert_eq!(tile_counts(size, tile_size), None);
+        }
+    }
+
+    #[test]
+    fn tile_counts_stay_within_the_cap() {
+        let (columns, rows) = tile_counts([1e9, 1e9], [1e-9, 1e-9]).unwrap();
+        assert_eq!((columns, rows), (MAX_TILES, 1));
+        let (columns, rows) = tile_counts([100.0, 1e6], [1.0, 1.0]).unwrap();
+        assert!(columns * rows <= MAX_TILES && columns == 100);
+    }
+}